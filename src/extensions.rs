@@ -0,0 +1,87 @@
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
+
+use crate::Error;
+
+/// The members of a JWK that aren't otherwise modeled, such as `ext` from WebCrypto or
+/// private `x-` members. These are preserved when the JWK is re-serialized.
+/// Registered members that don't apply to the key type are dropped.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct Extensions(Map<String, Value>);
+
+/// The members that are modeled by `JsonWebKey` and `Key`, and so can't be extensions.
+const REGISTERED_MEMBERS: &[&str] = &[
+    "kty", "use", "key_ops", "alg", "kid", "x5u", "x5c", "x5t", "x5t#S256", "crv", "x", "y", "d",
+    "n", "e", "p", "q", "dp", "dq", "qi", "k",
+];
+
+impl Extensions {
+    /// Deserializes the value of the extension `name`, if present.
+    pub fn get<T: DeserializeOwned>(&self, name: &str) -> Result<Option<T>, Error> {
+        Ok(self
+            .0
+            .get(name)
+            .map(|value| T::deserialize(value))
+            .transpose()?)
+    }
+
+    pub fn get_value(&self, name: &str) -> Option<&Value> {
+        self.0.get(name)
+    }
+
+    /// Sets the extension `name` to the serialized `value`.
+    /// Fails with `Error::RegisteredMember` if `name` is modeled by `JsonWebKey` or any `Key` type.
+    pub fn insert<T: Serialize>(&mut self, name: impl Into<String>, value: T) -> Result<(), Error> {
+        let name = name.into();
+        if REGISTERED_MEMBERS.contains(&name.as_str()) {
+            return Err(Error::RegisteredMember(name));
+        }
+        self.0.insert(name, serde_json::to_value(value)?);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Value> {
+        self.0.remove(name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.0.contains_key(name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.0.iter().map(|(name, value)| (name.as_str(), value))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Checks that every extension is one of the `understood` ones, for callers that must
+    /// not silently ignore members that may change how the key is to be used.
+    pub fn check_understood(&self, understood: &[&str]) -> Result<(), Error> {
+        match self
+            .0
+            .keys()
+            .find(|name| !understood.contains(&name.as_str()))
+        {
+            Some(name) => Err(Error::UnsupportedExtension(name.clone())),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Extensions {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let mut members = Map::deserialize(d)?;
+        // `Key` is also flattened into `JsonWebKey` but doesn't consume its members, so those
+        // are removed here, along with the members of the other key types (e.g., `x` in an
+        // `oct` key), which `insert` rejects too.
+        members.retain(|name, _| !REGISTERED_MEMBERS.contains(&name.as_str()));
+        Ok(Self(members))
+    }
+}
@@ -0,0 +1,131 @@
+use aes_gcm::{aead::Aead, Aes128Gcm, Aes256Gcm, KeyInit};
+use cbc::cipher::{block_padding::Pkcs7, BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sha2::Sha256;
+
+use super::{EncryptionAlgorithm, Error};
+
+const GCM_IV_LEN: usize = 12;
+const CBC_IV_LEN: usize = 16;
+const TAG_LEN: usize = 16;
+
+pub(super) struct Encrypted {
+    pub(super) iv: Vec<u8>,
+    pub(super) ciphertext: Vec<u8>,
+    pub(super) tag: Vec<u8>,
+}
+
+/// Encrypts `plaintext` using the content encryption key, as per
+/// [RFC 7518 §5](https://tools.ietf.org/html/rfc7518#section-5).
+pub(super) fn encrypt(
+    enc: EncryptionAlgorithm,
+    cek: &[u8],
+    aad: &[u8],
+    plaintext: &[u8],
+) -> Result<Encrypted, Error> {
+    if cek.len() != enc.key_len() {
+        return Err(Error::InvalidKey);
+    }
+    match enc {
+        EncryptionAlgorithm::A128GCM | EncryptionAlgorithm::A256GCM => {
+            let iv = random_iv(GCM_IV_LEN);
+            let payload = aes_gcm::aead::Payload {
+                msg: plaintext,
+                aad,
+            };
+            let mut ciphertext = match enc {
+                EncryptionAlgorithm::A128GCM => Aes128Gcm::new_from_slice(cek)
+                    .unwrap()
+                    .encrypt(iv[..].into(), payload),
+                _ => Aes256Gcm::new_from_slice(cek)
+                    .unwrap()
+                    .encrypt(iv[..].into(), payload),
+            }
+            .map_err(|_| Error::InvalidKey)?;
+            let tag = ciphertext.split_off(ciphertext.len() - TAG_LEN);
+            Ok(Encrypted {
+                iv,
+                ciphertext,
+                tag,
+            })
+        }
+        EncryptionAlgorithm::A128CbcHs256 => {
+            let (mac_key, enc_key) = cek.split_at(cek.len() / 2);
+            let iv = random_iv(CBC_IV_LEN);
+            let ciphertext = cbc::Encryptor::<aes::Aes128>::new_from_slices(enc_key, &iv)
+                .unwrap()
+                .encrypt_padded_vec_mut::<Pkcs7>(plaintext);
+            let tag = cbc_hmac(mac_key, aad, &iv, &ciphertext)
+                .finalize()
+                .into_bytes()[..TAG_LEN]
+                .to_vec();
+            Ok(Encrypted {
+                iv,
+                ciphertext,
+                tag,
+            })
+        }
+    }
+}
+
+/// Checks the authentication tag and decrypts the ciphertext.
+pub(super) fn decrypt(
+    enc: EncryptionAlgorithm,
+    cek: &[u8],
+    aad: &[u8],
+    iv: &[u8],
+    ciphertext: &[u8],
+    tag: &[u8],
+) -> Result<Vec<u8>, Error> {
+    if cek.len() != enc.key_len() {
+        return Err(Error::DecryptionFailed);
+    }
+    match enc {
+        EncryptionAlgorithm::A128GCM | EncryptionAlgorithm::A256GCM => {
+            if iv.len() != GCM_IV_LEN || tag.len() != TAG_LEN {
+                return Err(Error::Malformed);
+            }
+            let msg = [ciphertext, tag].concat();
+            let payload = aes_gcm::aead::Payload { msg: &msg, aad };
+            match enc {
+                EncryptionAlgorithm::A128GCM => Aes128Gcm::new_from_slice(cek)
+                    .unwrap()
+                    .decrypt(iv.into(), payload),
+                _ => Aes256Gcm::new_from_slice(cek)
+                    .unwrap()
+                    .decrypt(iv.into(), payload),
+            }
+            .map_err(|_| Error::DecryptionFailed)
+        }
+        EncryptionAlgorithm::A128CbcHs256 => {
+            if iv.len() != CBC_IV_LEN || tag.len() != TAG_LEN {
+                return Err(Error::Malformed);
+            }
+            let (mac_key, enc_key) = cek.split_at(cek.len() / 2);
+            cbc_hmac(mac_key, aad, iv, ciphertext)
+                .verify_truncated_left(tag)
+                .map_err(|_| Error::DecryptionFailed)?;
+            cbc::Decryptor::<aes::Aes128>::new_from_slices(enc_key, iv)
+                .unwrap()
+                .decrypt_padded_vec_mut::<Pkcs7>(ciphertext)
+                .map_err(|_| Error::DecryptionFailed)
+        }
+    }
+}
+
+fn random_iv(len: usize) -> Vec<u8> {
+    let mut iv = vec![0; len];
+    rand::thread_rng().fill_bytes(&mut iv);
+    iv
+}
+
+/// https://tools.ietf.org/html/rfc7518#section-5.2.2.1
+fn cbc_hmac(mac_key: &[u8], aad: &[u8], iv: &[u8], ciphertext: &[u8]) -> Hmac<Sha256> {
+    <Hmac<Sha256> as Mac>::new_from_slice(mac_key)
+        .expect("HMAC can take a key of any size")
+        .chain_update(aad)
+        .chain_update(iv)
+        .chain_update(ciphertext)
+        .chain_update((aad.len() as u64 * 8).to_be_bytes())
+}
@@ -0,0 +1,66 @@
+use zeroize::Zeroizing;
+
+use super::{
+    decrypt, decrypt_with_password, encrypt, encrypt_with_password, Decrypted, EncryptionAlgorithm,
+    Error, Header, KeyManagementAlgorithm,
+};
+use crate::JsonWebKey;
+
+/// The content type of an encrypted JWK, as per
+/// [RFC 7517 §7](https://tools.ietf.org/html/rfc7517#section-7).
+const JWK_CONTENT_TYPE: &str = "jwk+json";
+
+impl JsonWebKey {
+    /// Encrypts this key using a key derived from `password`, as per
+    /// [RFC 7517 §7](https://tools.ietf.org/html/rfc7517#section-7),
+    /// and returns the JWE compact serialization.
+    /// The JWE uses `PBES2-HS256+A128KW` with `DEFAULT_PBES2_COUNT` iterations and `A128CBC-HS256`.
+    pub fn encrypt_with_password(&self, password: impl AsRef<[u8]>) -> Result<String, Error> {
+        let header = jwk_header(KeyManagementAlgorithm::Pbes2Hs256A128KW);
+        encrypt_with_password(&header, self.to_json()?, password)
+    }
+
+    /// Decrypts a key that was encrypted using `encrypt_with_password`.
+    pub fn decrypt_with_password(jwe: &str, password: impl AsRef<[u8]>) -> Result<Self, Error> {
+        Self::from_decrypted(decrypt_with_password(jwe, password)?)
+    }
+
+    /// Encrypts this key to `wrapping_key` using `alg` and `A128CBC-HS256`, and returns the JWE
+    /// compact serialization. The `kid` of the wrapping key, if any, is included in the header.
+    pub fn encrypt_with_key(
+        &self,
+        alg: KeyManagementAlgorithm,
+        wrapping_key: &JsonWebKey,
+    ) -> Result<String, Error> {
+        let mut header = jwk_header(alg);
+        header.key_id = wrapping_key.key_id.clone();
+        encrypt(&header, self.to_json()?, wrapping_key)
+    }
+
+    /// Decrypts a key that was encrypted using `encrypt_with_key`.
+    pub fn decrypt_with_key(jwe: &str, wrapping_key: &JsonWebKey) -> Result<Self, Error> {
+        Self::from_decrypted(decrypt(jwe, wrapping_key)?)
+    }
+
+    fn to_json(&self) -> Result<Zeroizing<Vec<u8>>, Error> {
+        Ok(Zeroizing::new(serde_json::to_vec(self)?))
+    }
+
+    fn from_decrypted(decrypted: Decrypted) -> Result<Self, Error> {
+        let plaintext = Zeroizing::new(decrypted.plaintext);
+        let content_type = decrypted.header.content_type.as_deref().unwrap_or_default();
+        // https://tools.ietf.org/html/rfc7515#section-4.1.10
+        if !content_type.eq_ignore_ascii_case(JWK_CONTENT_TYPE)
+            && !content_type.eq_ignore_ascii_case(&format!("application/{}", JWK_CONTENT_TYPE))
+        {
+            return Err(Error::UnexpectedContentType);
+        }
+        Ok(Self::from_slice(&*plaintext)?)
+    }
+}
+
+fn jwk_header(alg: KeyManagementAlgorithm) -> Header {
+    let mut header = Header::new(alg, EncryptionAlgorithm::A128CbcHs256);
+    header.content_type = Some(JWK_CONTENT_TYPE.into());
+    header
+}
@@ -0,0 +1,365 @@
+use rand::RngCore;
+use rsa::Oaep;
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+use super::{Error, Header, KeyManagementAlgorithm};
+use crate::{utils::rsa_keys, ByteVec, Curve, JsonWebKey, Key, OkpCurve, PublicJwk};
+
+/// The PBES2 iteration count used when the header doesn't specify one.
+/// This is the count that OWASP recommends for PBKDF2-HMAC-SHA256.
+pub const DEFAULT_PBES2_COUNT: u32 = 600_000;
+
+/// The largest PBES2 iteration count accepted when decrypting. This bounds the work that an
+/// untrusted JWE can cause.
+pub const MAX_PBES2_COUNT: u32 = 10_000_000;
+
+const PBES2_SALT_LEN: usize = 16;
+
+/// https://tools.ietf.org/html/rfc7518#section-4.8.1.1
+const MIN_PBES2_SALT_LEN: usize = 8;
+
+/// Determines the content encryption key, as per
+/// [RFC 7518 §4](https://tools.ietf.org/html/rfc7518#section-4), and returns it along with
+/// the JWE Encrypted Key. Sets the `epk` of the header when using ECDH-ES.
+pub(super) fn encrypt_key(
+    header: &mut Header,
+    key: &Key,
+) -> Result<(Zeroizing<Vec<u8>>, Vec<u8>), Error> {
+    use KeyManagementAlgorithm::*;
+    let alg = header.algorithm;
+    match (alg, key) {
+        (Direct, Key::Symmetric { key }) => {
+            if key.len() != header.encryption.key_len() {
+                return Err(Error::InvalidKey);
+            }
+            Ok((Zeroizing::new(key.to_vec()), Vec::new()))
+        }
+        (A128KW | A256KW, Key::Symmetric { key }) => {
+            let cek = random_cek(header);
+            let encrypted_key = aes_wrap(alg, key, &cek)?;
+            Ok((cek, encrypted_key))
+        }
+        (RsaOaep | RsaOaep256, Key::RSA { public, .. }) => {
+            let key = rsa_keys::public_key(public).ok_or(Error::InvalidKey)?;
+            let cek = random_cek(header);
+            let encrypted_key = key
+                .encrypt(&mut rand::thread_rng(), oaep(alg), &cek)
+                .map_err(|_| Error::InvalidKey)?;
+            Ok((cek, encrypted_key))
+        }
+        (EcdhEs | EcdhEsA128KW | EcdhEsA256KW, Key::EC { .. } | Key::OKP { .. }) => {
+            let (ephemeral_key, shared_secret) = ecdh_ephemeral(key)?;
+            header.ephemeral_key = Some(Box::new(PublicJwk(JsonWebKey::new(ephemeral_key))));
+            let derived_key = concat_kdf(header, &shared_secret);
+            if alg == EcdhEs {
+                return Ok((derived_key, Vec::new()));
+            }
+            let cek = random_cek(header);
+            let encrypted_key = aes_wrap(alg, &derived_key, &cek)?;
+            Ok((cek, encrypted_key))
+        }
+        _ => Err(Error::Key(crate::Error::MismatchedAlgorithm)),
+    }
+}
+
+/// Recovers the content encryption key from the JWE Encrypted Key.
+pub(super) fn decrypt_key(
+    header: &Header,
+    key: &Key,
+    encrypted_key: &[u8],
+) -> Result<Zeroizing<Vec<u8>>, Error> {
+    use KeyManagementAlgorithm::*;
+    let alg = header.algorithm;
+    if matches!(alg, Direct | EcdhEs) && !encrypted_key.is_empty() {
+        return Err(Error::Malformed);
+    }
+    match (alg, key) {
+        (Direct, Key::Symmetric { key }) => Ok(Zeroizing::new(key.to_vec())),
+        (A128KW | A256KW, Key::Symmetric { key }) => aes_unwrap(alg, key, encrypted_key),
+        (
+            RsaOaep | RsaOaep256,
+            Key::RSA {
+                public,
+                private: Some(private),
+            },
+        ) => {
+            let key = rsa_keys::private_key(public, private).ok_or(Error::InvalidKey)?;
+            key.decrypt_blinded(&mut rand::thread_rng(), oaep(alg), encrypted_key)
+                .map(Zeroizing::new)
+                .map_err(|_| Error::DecryptionFailed)
+        }
+        (EcdhEs | EcdhEsA128KW | EcdhEsA256KW, Key::EC { .. } | Key::OKP { .. }) => {
+            let ephemeral_key = header
+                .ephemeral_key
+                .as_ref()
+                .ok_or(Error::InvalidEphemeralKey)?;
+            let shared_secret = ecdh_static(key, &ephemeral_key.key)?;
+            let derived_key = concat_kdf(header, &shared_secret);
+            if alg == EcdhEs {
+                return Ok(derived_key);
+            }
+            aes_unwrap(alg, &derived_key, encrypted_key)
+        }
+        _ => Err(Error::Key(crate::Error::MismatchedAlgorithm)),
+    }
+}
+
+/// Generates a content encryption key and wraps it using a key derived from `password`.
+/// Sets the `p2s` and `p2c` of the header if they're missing.
+pub(super) fn encrypt_key_with_password(
+    header: &mut Header,
+    password: &[u8],
+) -> Result<(Zeroizing<Vec<u8>>, Vec<u8>), Error> {
+    if header.algorithm != KeyManagementAlgorithm::Pbes2Hs256A128KW {
+        return Err(Error::Key(crate::Error::MismatchedAlgorithm));
+    }
+    if header.pbes2_salt.is_none() {
+        let mut salt = vec![0; PBES2_SALT_LEN];
+        rand::thread_rng().fill_bytes(&mut salt);
+        header.pbes2_salt = Some(salt.into());
+    }
+    header.pbes2_count.get_or_insert(DEFAULT_PBES2_COUNT);
+    let kek = pbes2_key(header, password)?;
+    let cek = random_cek(header);
+    let encrypted_key = aes_wrap(header.algorithm, &kek, &cek)?;
+    Ok((cek, encrypted_key))
+}
+
+pub(super) fn decrypt_key_with_password(
+    header: &Header,
+    password: &[u8],
+    encrypted_key: &[u8],
+) -> Result<Zeroizing<Vec<u8>>, Error> {
+    if header.algorithm != KeyManagementAlgorithm::Pbes2Hs256A128KW {
+        return Err(Error::Key(crate::Error::MismatchedAlgorithm));
+    }
+    let kek = pbes2_key(header, password)?;
+    aes_unwrap(header.algorithm, &kek, encrypted_key)
+}
+
+/// Derives the key wrapping key from `password`, as per
+/// [RFC 7518 §4.8](https://tools.ietf.org/html/rfc7518#section-4.8).
+fn pbes2_key(header: &Header, password: &[u8]) -> Result<Zeroizing<Vec<u8>>, Error> {
+    let (Some(salt), Some(count)) = (&header.pbes2_salt, header.pbes2_count) else {
+        return Err(Error::InvalidPbes2Params);
+    };
+    if salt.len() < MIN_PBES2_SALT_LEN || count == 0 || count > MAX_PBES2_COUNT {
+        return Err(Error::InvalidPbes2Params);
+    }
+    let salt = [header.algorithm.name().as_bytes(), &[0], salt].concat();
+    let mut kek = Zeroizing::new(vec![0; header.algorithm.wrap_key_len().unwrap()]);
+    pbkdf2::pbkdf2_hmac::<Sha256>(password, &salt, count, &mut kek);
+    Ok(kek)
+}
+
+fn random_cek(header: &Header) -> Zeroizing<Vec<u8>> {
+    let mut cek = Zeroizing::new(vec![0; header.encryption.key_len()]);
+    rand::thread_rng().fill_bytes(&mut cek);
+    cek
+}
+
+fn oaep(alg: KeyManagementAlgorithm) -> Oaep {
+    match alg {
+        KeyManagementAlgorithm::RsaOaep => Oaep::new::<sha1::Sha1>(),
+        _ => Oaep::new::<Sha256>(),
+    }
+}
+
+/// Evaluates `$body` with `$kek` bound to the AES key wrapping key for `$alg`.
+macro_rules! with_kek {
+    ($alg:expr, $kek:ident, $body:expr) => {{
+        if Some($kek.len()) != $alg.wrap_key_len() {
+            return Err(Error::InvalidKey);
+        }
+        match $kek.len() {
+            16 => {
+                let $kek = aes_kw::KekAes128::from(<[u8; 16]>::try_from($kek).unwrap());
+                $body
+            }
+            _ => {
+                let $kek = aes_kw::KekAes256::from(<[u8; 32]>::try_from($kek).unwrap());
+                $body
+            }
+        }
+    }};
+}
+
+/// https://tools.ietf.org/html/rfc7518#section-4.4
+fn aes_wrap(alg: KeyManagementAlgorithm, kek: &[u8], cek: &[u8]) -> Result<Vec<u8>, Error> {
+    with_kek!(alg, kek, kek.wrap_vec(cek)).map_err(|_| Error::InvalidKey)
+}
+
+fn aes_unwrap(
+    alg: KeyManagementAlgorithm,
+    kek: &[u8],
+    encrypted_key: &[u8],
+) -> Result<Zeroizing<Vec<u8>>, Error> {
+    with_kek!(alg, kek, kek.unwrap_vec(encrypted_key))
+        .map(Zeroizing::new)
+        .map_err(|_| Error::DecryptionFailed)
+}
+
+/// Generates an ephemeral key on the curve of `key` and returns its public part along with the
+/// secret shared with `key`.
+fn ecdh_ephemeral(key: &Key) -> Result<(Key, Zeroizing<Vec<u8>>), Error> {
+    match key {
+        Key::EC { curve, x, y, .. } => {
+            macro_rules! ecdh {
+                ($krate:ident) => {{
+                    let public = $krate::PublicKey::from_sec1_bytes(&ec_point(x, y))
+                        .map_err(|_| Error::InvalidKey)?;
+                    let secret = $krate::SecretKey::random(&mut rand::thread_rng());
+                    let shared_secret = $krate::ecdh::diffie_hellman(
+                        secret.to_nonzero_scalar(),
+                        public.as_affine(),
+                    );
+                    let ephemeral_key = Key::from_ec_secret_key(*curve, &secret);
+                    (ephemeral_key, shared_secret.raw_secret_bytes().to_vec())
+                }};
+            }
+            let (ephemeral_key, shared_secret) = match curve {
+                Curve::P256 => ecdh!(p256),
+                Curve::P384 => ecdh!(p384),
+                Curve::P521 => ecdh!(p521),
+                Curve::Secp256k1 => return Err(Error::UnsupportedCurve(curve.name())),
+            };
+            let ephemeral_key = ephemeral_key.to_public().unwrap().into_owned();
+            Ok((ephemeral_key, Zeroizing::new(shared_secret)))
+        }
+        Key::OKP {
+            curve: OkpCurve::X25519,
+            x,
+            ..
+        } => {
+            let public = x25519_dalek::PublicKey::from(x25519_bytes(x, Error::InvalidKey)?);
+            let secret = x25519_dalek::StaticSecret::random_from_rng(rand::thread_rng());
+            let shared_secret = secret.diffie_hellman(&public);
+            if !shared_secret.was_contributory() {
+                return Err(Error::InvalidKey);
+            }
+            let ephemeral_key = Key::OKP {
+                curve: OkpCurve::X25519,
+                d: None,
+                x: x25519_dalek::PublicKey::from(&secret)
+                    .to_bytes()
+                    .to_vec()
+                    .into(),
+            };
+            Ok((
+                ephemeral_key,
+                Zeroizing::new(shared_secret.to_bytes().to_vec()),
+            ))
+        }
+        Key::OKP { curve, .. } => Err(Error::UnsupportedCurve(curve.name())),
+        _ => Err(Error::Key(crate::Error::MismatchedAlgorithm)),
+    }
+}
+
+/// Returns the secret shared by the private `key` and the sender's `ephemeral_key`.
+fn ecdh_static(key: &Key, ephemeral_key: &Key) -> Result<Zeroizing<Vec<u8>>, Error> {
+    match (key, ephemeral_key) {
+        (
+            Key::EC {
+                curve, d: Some(d), ..
+            },
+            Key::EC {
+                curve: ephemeral_curve,
+                x,
+                y,
+                ..
+            },
+        ) if curve == ephemeral_curve => {
+            macro_rules! ecdh {
+                ($krate:ident) => {{
+                    let secret = $krate::SecretKey::from_slice(d).map_err(|_| Error::InvalidKey)?;
+                    let public = $krate::PublicKey::from_sec1_bytes(&ec_point(x, y))
+                        .map_err(|_| Error::InvalidEphemeralKey)?;
+                    $krate::ecdh::diffie_hellman(secret.to_nonzero_scalar(), public.as_affine())
+                        .raw_secret_bytes()
+                        .to_vec()
+                }};
+            }
+            Ok(Zeroizing::new(match curve {
+                Curve::P256 => ecdh!(p256),
+                Curve::P384 => ecdh!(p384),
+                Curve::P521 => ecdh!(p521),
+                Curve::Secp256k1 => return Err(Error::UnsupportedCurve(curve.name())),
+            }))
+        }
+        (
+            Key::OKP {
+                curve: OkpCurve::X25519,
+                d: Some(d),
+                ..
+            },
+            Key::OKP {
+                curve: OkpCurve::X25519,
+                x,
+                ..
+            },
+        ) => {
+            let secret = x25519_dalek::StaticSecret::from(x25519_bytes(d, Error::InvalidKey)?);
+            let public =
+                x25519_dalek::PublicKey::from(x25519_bytes(x, Error::InvalidEphemeralKey)?);
+            let shared_secret = secret.diffie_hellman(&public);
+            if !shared_secret.was_contributory() {
+                return Err(Error::InvalidEphemeralKey);
+            }
+            Ok(Zeroizing::new(shared_secret.to_bytes().to_vec()))
+        }
+        (Key::OKP { curve, .. }, _) if *curve != OkpCurve::X25519 => {
+            Err(Error::UnsupportedCurve(curve.name()))
+        }
+        _ => Err(Error::InvalidEphemeralKey),
+    }
+}
+
+fn ec_point(x: &ByteVec, y: &ByteVec) -> Vec<u8> {
+    [0x04 /* uncompressed */]
+        .iter()
+        .chain(x.iter())
+        .chain(y.iter())
+        .copied()
+        .collect()
+}
+
+fn x25519_bytes(bytes: &ByteVec, err: Error) -> Result<[u8; 32], Error> {
+    bytes.as_ref().try_into().map_err(|_| err)
+}
+
+/// Derives the key agreed upon using ECDH-ES, as per
+/// [RFC 7518 §4.6.2](https://tools.ietf.org/html/rfc7518#section-4.6.2).
+pub(crate) fn concat_kdf(header: &Header, shared_secret: &[u8]) -> Zeroizing<Vec<u8>> {
+    let (algorithm_id, key_len) = match header.algorithm.wrap_key_len() {
+        Some(key_len) => (header.algorithm.name(), key_len),
+        None => (header.encryption.name(), header.encryption.key_len()),
+    };
+
+    let mut other_info = Vec::new();
+    for field in [
+        algorithm_id.as_bytes(),
+        header.party_u_info.as_deref().unwrap_or_default(),
+        header.party_v_info.as_deref().unwrap_or_default(),
+    ] {
+        other_info.extend_from_slice(&(field.len() as u32).to_be_bytes());
+        other_info.extend_from_slice(field);
+    }
+    other_info.extend_from_slice(&(key_len as u32 * 8).to_be_bytes());
+
+    let mut derived_key = Zeroizing::new(Vec::with_capacity(key_len));
+    for counter in 1u32.. {
+        if derived_key.len() >= key_len {
+            break;
+        }
+        derived_key.extend_from_slice(
+            &Sha256::new()
+                .chain_update(counter.to_be_bytes())
+                .chain_update(shared_secret)
+                .chain_update(&other_info)
+                .finalize(),
+        );
+    }
+    derived_key.truncate(key_len);
+    derived_key
+}
@@ -0,0 +1,370 @@
+//! JSON Web Encryption in the compact serialization, as per
+//! [RFC 7516](https://tools.ietf.org/html/rfc7516).
+//! Keys can be encrypted for storage using `JsonWebKey::encrypt_with_password`.
+//!
+//! ```
+//! # #[cfg(feature = "generate")] {
+//! use jsonwebkey::{jwe, JsonWebKey, Key};
+//!
+//! let jwk = JsonWebKey::new(Key::generate_p256());
+//! let header = jwe::Header::new(
+//!     jwe::KeyManagementAlgorithm::EcdhEsA128KW,
+//!     jwe::EncryptionAlgorithm::A128GCM,
+//! );
+//! let token = jwe::encrypt(&header, b"hello", &jwk).unwrap();
+//! let decrypted = jwe::decrypt(&token, &jwk).unwrap();
+//! assert_eq!(decrypted.plaintext, b"hello");
+//! # }
+//! ```
+
+mod content;
+mod encrypted_jwk;
+pub(crate) mod key_management;
+
+pub use crate::KeyManagementAlgorithm;
+pub use key_management::{DEFAULT_PBES2_COUNT, MAX_PBES2_COUNT};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    utils::{base64_decode, base64_encode},
+    ByteVec, JsonWebKey, KeyAlgorithm, KeyHeader, KeyOps, PublicJwk,
+};
+
+/// The algorithm used to encrypt the content, as per
+/// [RFC 7518 §5.1](https://tools.ietf.org/html/rfc7518#section-5.1).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[allow(clippy::upper_case_acronyms)]
+pub enum EncryptionAlgorithm {
+    /// AES-128-CBC with HMAC-SHA-256 truncated to 128 bits.
+    #[serde(rename = "A128CBC-HS256")]
+    A128CbcHs256,
+    A128GCM,
+    A256GCM,
+}
+
+impl EncryptionAlgorithm {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::A128CbcHs256 => "A128CBC-HS256",
+            Self::A128GCM => "A128GCM",
+            Self::A256GCM => "A256GCM",
+        }
+    }
+
+    /// The number of bytes in the content encryption key.
+    pub fn key_len(&self) -> usize {
+        match self {
+            Self::A128CbcHs256 => 32,
+            Self::A128GCM => 16,
+            Self::A256GCM => 32,
+        }
+    }
+}
+
+impl std::fmt::Display for EncryptionAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The JOSE header of a JWE, as per [RFC 7516 §4](https://tools.ietf.org/html/rfc7516#section-4).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Header {
+    #[serde(rename = "alg")]
+    pub algorithm: KeyManagementAlgorithm,
+
+    #[serde(rename = "enc")]
+    pub encryption: EncryptionAlgorithm,
+
+    #[serde(default, rename = "kid", skip_serializing_if = "Option::is_none")]
+    pub key_id: Option<String>,
+
+    #[serde(default, rename = "typ", skip_serializing_if = "Option::is_none")]
+    pub token_type: Option<String>,
+
+    #[serde(default, rename = "cty", skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+
+    /// jwk: The public key to which the JWE was encrypted.
+    /// This is a `PublicJwk` so that a private key can't be embedded in the token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jwk: Option<Box<PublicJwk>>,
+
+    /// x5t: The SHA-1 thumbprint of the DER-encoded X.509 certificate of the recipient's key.
+    #[serde(default, rename = "x5t", skip_serializing_if = "Option::is_none")]
+    pub cert_thumbprint: Option<String>,
+
+    /// x5t#S256: The same data as the thumbprint, but digested using SHA-256
+    #[serde(default, rename = "x5t#S256", skip_serializing_if = "Option::is_none")]
+    pub cert_thumbprint_sha256: Option<String>,
+
+    /// epk: The ephemeral public key used for ECDH-ES. This is set by `encrypt`.
+    /// This is a `PublicJwk` so that a private key can't be embedded in the token.
+    #[serde(default, rename = "epk", skip_serializing_if = "Option::is_none")]
+    pub ephemeral_key: Option<Box<PublicJwk>>,
+
+    /// apu: Information about the producer, used in ECDH-ES key derivation.
+    #[serde(default, rename = "apu", skip_serializing_if = "Option::is_none")]
+    pub party_u_info: Option<ByteVec>,
+
+    /// apv: Information about the recipient, used in ECDH-ES key derivation.
+    #[serde(default, rename = "apv", skip_serializing_if = "Option::is_none")]
+    pub party_v_info: Option<ByteVec>,
+
+    /// p2s: The salt input used for PBES2 key derivation. This is set by `encrypt_with_password`.
+    #[serde(default, rename = "p2s", skip_serializing_if = "Option::is_none")]
+    pub pbes2_salt: Option<ByteVec>,
+
+    /// p2c: The PBES2 iteration count. This is set by `encrypt_with_password`.
+    #[serde(default, rename = "p2c", skip_serializing_if = "Option::is_none")]
+    pub pbes2_count: Option<u32>,
+
+    /// crit: The names of the extension parameters that must be understood by the recipient.
+    #[serde(default, rename = "crit", skip_serializing_if = "Vec::is_empty")]
+    pub critical: Vec<String>,
+
+    /// Any other header parameters.
+    #[serde(flatten)]
+    pub additional: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Header {
+    pub fn new(alg: KeyManagementAlgorithm, enc: EncryptionAlgorithm) -> Self {
+        Self {
+            algorithm: alg,
+            encryption: enc,
+            key_id: None,
+            token_type: None,
+            content_type: None,
+            jwk: None,
+            cert_thumbprint: None,
+            cert_thumbprint_sha256: None,
+            ephemeral_key: None,
+            party_u_info: None,
+            party_v_info: None,
+            pbes2_salt: None,
+            pbes2_count: None,
+            critical: Vec::new(),
+            additional: Default::default(),
+        }
+    }
+
+    /// Returns the parameters used to select the decryption key using `JwkSet::select`,
+    /// along with `self.algorithm.decryption_ops()`.
+    pub fn key_header(&self) -> KeyHeader {
+        KeyHeader {
+            algorithm: Some(self.algorithm.into()),
+            key_id: self.key_id.clone(),
+            cert_thumbprint: self.cert_thumbprint.clone(),
+            cert_thumbprint_sha256: self.cert_thumbprint_sha256.clone(),
+            jwk: self.jwk.clone().map(|jwk| Box::new(JsonWebKey::from(*jwk))),
+        }
+    }
+}
+
+/// The contents of a JWE that has been decrypted.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Decrypted {
+    pub header: Header,
+    pub plaintext: Vec<u8>,
+}
+
+/// Encrypts `plaintext` to `jwk` and returns the JWE compact serialization.
+///
+/// The key must be suitable for `header.algorithm`: RSA for `RSA-OAEP*`, a symmetric key of the
+/// right size for `A*KW` and `dir`, and a P-256, P-384, P-521, or X25519 key for `ECDH-ES*`.
+/// Its `use` and `key_ops`, if present, must permit `header.algorithm.encryption_ops()`.
+pub fn encrypt(
+    header: &Header,
+    plaintext: impl AsRef<[u8]>,
+    jwk: &JsonWebKey,
+) -> Result<String, Error> {
+    check_key(jwk, header.algorithm, header.algorithm.encryption_ops())?;
+    check_header(header)?;
+    let mut header = header.clone();
+    let (cek, encrypted_key) = key_management::encrypt_key(&mut header, &jwk.key)?;
+    serialize(&header, &cek, &encrypted_key, plaintext.as_ref())
+}
+
+/// Encrypts `plaintext` using a key derived from `password` and returns the JWE compact
+/// serialization. `header.algorithm` must be `PBES2-HS256+A128KW`.
+///
+/// The salt (`p2s`) is randomly generated unless set, and the iteration count (`p2c`)
+/// defaults to `DEFAULT_PBES2_COUNT`.
+pub fn encrypt_with_password(
+    header: &Header,
+    plaintext: impl AsRef<[u8]>,
+    password: impl AsRef<[u8]>,
+) -> Result<String, Error> {
+    check_header(header)?;
+    let mut header = header.clone();
+    let (cek, encrypted_key) =
+        key_management::encrypt_key_with_password(&mut header, password.as_ref())?;
+    serialize(&header, &cek, &encrypted_key, plaintext.as_ref())
+}
+
+fn serialize(
+    header: &Header,
+    cek: &[u8],
+    encrypted_key: &[u8],
+    plaintext: &[u8],
+) -> Result<String, Error> {
+    let encoded_header = base64_encode(serde_json::to_vec(header)?);
+    let encrypted = content::encrypt(header.encryption, cek, encoded_header.as_bytes(), plaintext)?;
+    Ok([
+        encoded_header,
+        base64_encode(encrypted_key),
+        base64_encode(encrypted.iv),
+        base64_encode(encrypted.ciphertext),
+        base64_encode(encrypted.tag),
+    ]
+    .join("."))
+}
+
+/// Decodes the header of a compact JWE without decrypting it.
+/// This is useful for selecting the decryption key from a `JwkSet`.
+pub fn decode_header(jwe: &str) -> Result<Header, Error> {
+    let (header, _) = jwe.split_once('.').ok_or(Error::Malformed)?;
+    parse_header(header)
+}
+
+/// Decrypts a JWE in compact serialization using `jwk` and returns its header and plaintext.
+pub fn decrypt(jwe: &str, jwk: &JsonWebKey) -> Result<Decrypted, Error> {
+    let parts = Parts::parse(jwe)?;
+    check_key(
+        jwk,
+        parts.header.algorithm,
+        parts.header.algorithm.decryption_ops(),
+    )?;
+    let cek = key_management::decrypt_key(&parts.header, &jwk.key, &parts.encrypted_key)?;
+    parts.decrypt(&cek)
+}
+
+/// Decrypts a JWE in compact serialization that was encrypted using `encrypt_with_password`.
+pub fn decrypt_with_password(jwe: &str, password: impl AsRef<[u8]>) -> Result<Decrypted, Error> {
+    let parts = Parts::parse(jwe)?;
+    let cek = key_management::decrypt_key_with_password(
+        &parts.header,
+        password.as_ref(),
+        &parts.encrypted_key,
+    )?;
+    parts.decrypt(&cek)
+}
+
+/// The decoded parts of a JWE in compact serialization.
+struct Parts<'a> {
+    encoded_header: &'a str,
+    header: Header,
+    encrypted_key: Vec<u8>,
+    iv: Vec<u8>,
+    ciphertext: Vec<u8>,
+    tag: Vec<u8>,
+}
+
+impl<'a> Parts<'a> {
+    fn parse(jwe: &'a str) -> Result<Self, Error> {
+        let parts: Vec<&str> = jwe.split('.').collect();
+        let [encoded_header, encrypted_key, iv, ciphertext, tag] = parts[..] else {
+            return Err(Error::Malformed);
+        };
+        let decode = |part: &str| base64_decode(part).map_err(|_| Error::Malformed);
+        Ok(Self {
+            encoded_header,
+            header: parse_header(encoded_header)?,
+            encrypted_key: decode(encrypted_key)?,
+            iv: decode(iv)?,
+            ciphertext: decode(ciphertext)?,
+            tag: decode(tag)?,
+        })
+    }
+
+    fn decrypt(self, cek: &[u8]) -> Result<Decrypted, Error> {
+        let plaintext = content::decrypt(
+            self.header.encryption,
+            cek,
+            self.encoded_header.as_bytes(),
+            &self.iv,
+            &self.ciphertext,
+            &self.tag,
+        )?;
+        Ok(Decrypted {
+            header: self.header,
+            plaintext,
+        })
+    }
+}
+
+fn parse_header(header: &str) -> Result<Header, Error> {
+    let header: Header =
+        serde_json::from_slice(&base64_decode(header).map_err(|_| Error::Malformed)?)?;
+    check_header(&header)?;
+    Ok(header)
+}
+
+/// No extension parameters are understood, and compression is not supported.
+fn check_header(header: &Header) -> Result<(), Error> {
+    if let Some(param) = header.critical.first() {
+        return Err(Error::UnsupportedCriticalParam(param.clone()));
+    }
+    if header.additional.contains_key("zip") {
+        return Err(Error::UnsupportedCompression);
+    }
+    Ok(())
+}
+
+/// Checks that `jwk` may be used to perform `ops` using `alg`.
+fn check_key(jwk: &JsonWebKey, alg: KeyManagementAlgorithm, ops: KeyOps) -> Result<(), Error> {
+    if !jwk.permits(&ops) {
+        return Err(Error::OperationNotPermitted(ops));
+    }
+    match jwk.algorithm {
+        Some(key_alg) if key_alg != alg.into() => Err(Error::AlgorithmNotPermitted(key_alg)),
+        _ => Ok(()),
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("malformed JWE")]
+    Malformed,
+
+    #[error("invalid JWE header: {0}")]
+    Header(#[from] serde_json::Error),
+
+    #[error("unsupported critical header parameter: `{0}`")]
+    UnsupportedCriticalParam(String),
+
+    #[error("compressed JWEs are not supported")]
+    UnsupportedCompression,
+
+    #[error("the key can not be used for {0:?}")]
+    OperationNotPermitted(KeyOps),
+
+    #[error("the key's `alg` is {0:?}, which does not match the JWE algorithm")]
+    AlgorithmNotPermitted(KeyAlgorithm),
+
+    #[error(transparent)]
+    Key(#[from] crate::Error),
+
+    #[error("invalid key")]
+    InvalidKey,
+
+    #[error("ECDH-ES using {0} keys is not supported")]
+    UnsupportedCurve(&'static str),
+
+    #[error("missing or invalid ephemeral public key")]
+    InvalidEphemeralKey,
+
+    #[error(
+        "`p2s` must have at least 8 bytes, and `p2c` must be between 1 and {}",
+        MAX_PBES2_COUNT
+    )]
+    InvalidPbes2Params,
+
+    #[error("expected the content type to be `jwk+json`")]
+    UnexpectedContentType,
+
+    #[error("decryption failed")]
+    DecryptionFailed,
+}
@@ -0,0 +1,195 @@
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::{Algorithm, Error, JsonWebKey, KeyUse, PrivateJwk, PublicJwk};
+
+/// A JWK Set, as per [RFC 7517 §5](https://tools.ietf.org/html/rfc7517#section-5).
+///
+/// Members that can't be parsed as a `JsonWebKey` (e.g., because they have an unknown `kty`,
+/// or a `use` and `key_ops` that contradict each other) don't fail the whole set. Instead,
+/// they're kept as-is so that the set round-trips and can be inspected using `JwkSet::unparsed`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct JwkSet {
+    #[serde(rename = "keys")]
+    members: Vec<Member>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(untagged)]
+enum Member {
+    Key(JsonWebKey),
+    Unparsed(serde_json::Value),
+}
+
+impl<'de> Deserialize<'de> for Member {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let value = serde_json::Value::deserialize(d)?;
+        let jwk = JsonWebKey::deserialize(&value)
+            .ok()
+            .filter(|jwk| match jwk.algorithm {
+                Some(alg) => JsonWebKey::validate_key_algorithm(alg, &jwk.key).is_ok(),
+                None => true,
+            })
+            .filter(|jwk| jwk.check_key_usage().is_ok());
+        Ok(match jwk {
+            Some(jwk) => Self::Key(jwk),
+            None => Self::Unparsed(value),
+        })
+    }
+}
+
+impl JwkSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_slice(bytes: impl AsRef<[u8]>) -> Result<Self, Error> {
+        Ok(serde_json::from_slice(bytes.as_ref())?)
+    }
+
+    pub fn push(&mut self, jwk: JsonWebKey) {
+        self.members.push(Member::Key(jwk));
+    }
+
+    /// Returns the keys that were successfully parsed, in order.
+    pub fn keys(&self) -> impl Iterator<Item = &JsonWebKey> {
+        self.members.iter().filter_map(|member| match member {
+            Member::Key(jwk) => Some(jwk),
+            Member::Unparsed(_) => None,
+        })
+    }
+
+    /// Returns the members that could not be parsed as a `JsonWebKey`, in order.
+    pub fn unparsed(&self) -> impl Iterator<Item = &serde_json::Value> {
+        self.members.iter().filter_map(|member| match member {
+            Member::Key(_) => None,
+            Member::Unparsed(value) => Some(value),
+        })
+    }
+
+    /// Returns the first key having the provided `kid`.
+    pub fn find_by_key_id(&self, key_id: &str) -> Option<&JsonWebKey> {
+        self.keys()
+            .find(|jwk| jwk.key_id.as_deref() == Some(key_id))
+    }
+
+    /// Returns the first key having the provided SHA-256 JWK thumbprint.
+    #[cfg(feature = "thumbprint")]
+    pub fn find_by_thumbprint(&self, thumbprint: &str) -> Option<&JsonWebKey> {
+        self.keys().find(|jwk| jwk.key.thumbprint() == thumbprint)
+    }
+
+    /// Returns the keys intended for the provided `use`, including keys that don't specify one.
+    pub fn filter_by_use(&self, key_use: KeyUse) -> impl Iterator<Item = &JsonWebKey> {
+        self.keys()
+            .filter(move |jwk| jwk.key_use.is_none() || jwk.key_use.as_ref() == Some(&key_use))
+    }
+
+    /// Returns the keys usable with the provided algorithm. Keys that don't specify an `alg`
+    /// are included if their key type is suitable for the algorithm.
+    pub fn filter_by_algorithm(&self, alg: Algorithm) -> impl Iterator<Item = &JsonWebKey> {
+        self.keys().filter(move |jwk| match jwk.algorithm {
+            Some(key_alg) => key_alg == alg.into(),
+            None => JsonWebKey::validate_algorithm(alg, &jwk.key).is_ok(),
+        })
+    }
+
+    /// Returns the set of the public keys, for publishing. Private keys are replaced by
+    /// their public keys (as per `PrivateJwk::to_public`), and symmetric keys are omitted,
+    /// as are the members that couldn't be parsed and the keys that can't be converted to a
+    /// `PrivateJwk` or `PublicJwk` (e.g., because their `alg` doesn't fit the key).
+    pub fn to_public(&self) -> PublicJwkSet {
+        self.keys()
+            .filter_map(|jwk| {
+                if jwk.key.is_private() {
+                    PrivateJwk::try_from(jwk.clone())
+                        .ok()
+                        .map(|jwk| jwk.to_public())
+                } else {
+                    PublicJwk::try_from(jwk.clone()).ok()
+                }
+            })
+            .collect()
+    }
+}
+
+impl From<Vec<JsonWebKey>> for JwkSet {
+    fn from(keys: Vec<JsonWebKey>) -> Self {
+        keys.into_iter().collect()
+    }
+}
+
+impl FromIterator<JsonWebKey> for JwkSet {
+    fn from_iter<I: IntoIterator<Item = JsonWebKey>>(keys: I) -> Self {
+        Self {
+            members: keys.into_iter().map(Member::Key).collect(),
+        }
+    }
+}
+
+impl std::str::FromStr for JwkSet {
+    type Err = Error;
+    fn from_str(json: &str) -> Result<Self, Self::Err> {
+        Self::from_slice(json.as_bytes())
+    }
+}
+
+impl std::fmt::Display for JwkSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if f.alternate() {
+            write!(f, "{}", serde_json::to_string_pretty(self).unwrap())
+        } else {
+            write!(f, "{}", serde_json::to_string(self).unwrap())
+        }
+    }
+}
+
+/// A JWK Set that contains only public keys, and so is safe to publish.
+/// Unlike `JwkSet`, deserializing fails if any member is not a public key.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicJwkSet {
+    keys: Vec<PublicJwk>,
+}
+
+impl PublicJwkSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, jwk: PublicJwk) {
+        self.keys.push(jwk);
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &PublicJwk> {
+        self.keys.iter()
+    }
+}
+
+impl From<Vec<PublicJwk>> for PublicJwkSet {
+    fn from(keys: Vec<PublicJwk>) -> Self {
+        Self { keys }
+    }
+}
+
+impl FromIterator<PublicJwk> for PublicJwkSet {
+    fn from_iter<I: IntoIterator<Item = PublicJwk>>(keys: I) -> Self {
+        Self {
+            keys: keys.into_iter().collect(),
+        }
+    }
+}
+
+impl From<PublicJwkSet> for JwkSet {
+    fn from(jwks: PublicJwkSet) -> Self {
+        jwks.keys.into_iter().map(JsonWebKey::from).collect()
+    }
+}
+
+impl std::fmt::Display for PublicJwkSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if f.alternate() {
+            write!(f, "{}", serde_json::to_string_pretty(self).unwrap())
+        } else {
+            write!(f, "{}", serde_json::to_string(self).unwrap())
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use hmac::{Hmac, Mac};
+use rsa::{
+    signature::{RandomizedSigner, SignatureEncoding, Signer, Verifier},
+    RsaPrivateKey, RsaPublicKey,
+};
+use sha2::{
+    digest::{const_oid::AssociatedOid, FixedOutputReset},
+    Digest, Sha256, Sha384, Sha512,
+};
+
+use super::Error;
+use crate::{utils::rsa_keys, Algorithm, ByteVec, Curve, Key, OkpCurve};
+
+macro_rules! hmac {
+    ($digest:ty, $key:expr, $message:expr) => {
+        Hmac::<$digest>::new_from_slice($key)
+            .expect("HMAC can take a key of any size")
+            .chain_update($message)
+    };
+}
+
+/// Computes the JWS signature of `message`, as per
+/// [RFC 7518 §3](https://tools.ietf.org/html/rfc7518#section-3).
+/// The caller is responsible for checking that `alg` is suitable for `key`.
+pub(crate) fn sign(alg: Algorithm, key: &Key, message: &[u8]) -> Result<Vec<u8>, Error> {
+    use Algorithm::*;
+    match (alg, key) {
+        (HS256, Key::Symmetric { key }) => {
+            Ok(hmac!(Sha256, key, message).finalize().into_bytes().to_vec())
+        }
+        (HS384, Key::Symmetric { key }) => {
+            Ok(hmac!(Sha384, key, message).finalize().into_bytes().to_vec())
+        }
+        (HS512, Key::Symmetric { key }) => {
+            Ok(hmac!(Sha512, key, message).finalize().into_bytes().to_vec())
+        }
+        (
+            RS256 | RS384 | RS512 | PS256 | PS384 | PS512,
+            Key::RSA {
+                public,
+                private: Some(private),
+            },
+        ) => {
+            let key = rsa_keys::private_key(public, private).ok_or(Error::InvalidKey)?;
+            match alg {
+                RS256 => rsa_sign::<Sha256>(key, false, message),
+                RS384 => rsa_sign::<Sha384>(key, false, message),
+                RS512 => rsa_sign::<Sha512>(key, false, message),
+                PS256 => rsa_sign::<Sha256>(key, true, message),
+                PS384 => rsa_sign::<Sha384>(key, true, message),
+                _ => rsa_sign::<Sha512>(key, true, message),
+            }
+        }
+        (
+            ES256 | ES384 | ES512 | ES256K,
+            Key::EC {
+                curve, d: Some(d), ..
+            },
+        ) => {
+            macro_rules! ecdsa_sign {
+                ($krate:ident) => {{
+                    let key =
+                        $krate::ecdsa::SigningKey::from_slice(d).map_err(|_| Error::InvalidKey)?;
+                    let signature: $krate::ecdsa::Signature = key.sign(message);
+                    signature.to_vec()
+                }};
+            }
+            Ok(match curve {
+                Curve::P256 => ecdsa_sign!(p256),
+                Curve::P384 => ecdsa_sign!(p384),
+                Curve::P521 => ecdsa_sign!(p521),
+                Curve::Secp256k1 => ecdsa_sign!(k256),
+            })
+        }
+        (
+            EdDSA,
+            Key::OKP {
+                curve, d: Some(d), ..
+            },
+        ) => {
+            let key = ed25519_dalek::SigningKey::from_bytes(&ed25519_bytes(*curve, d)?);
+            Ok(key.sign(message).to_vec())
+        }
+        _ => Err(Error::Key(crate::Error::MismatchedAlgorithm)),
+    }
+}
+
+/// Checks the JWS `signature` of `message`.
+/// The caller is responsible for checking that `alg` is suitable for `key`.
+pub(crate) fn verify(
+    alg: Algorithm,
+    key: &Key,
+    message: &[u8],
+    signature: &[u8],
+) -> Result<(), Error> {
+    use Algorithm::*;
+    match (alg, key) {
+        (HS256 | HS384 | HS512, Key::Symmetric { key }) => match alg {
+            HS256 => hmac!(Sha256, key, message).verify_slice(signature),
+            HS384 => hmac!(Sha384, key, message).verify_slice(signature),
+            _ => hmac!(Sha512, key, message).verify_slice(signature),
+        }
+        .map_err(|_| Error::InvalidSignature),
+        (RS256 | RS384 | RS512 | PS256 | PS384 | PS512, Key::RSA { public, .. }) => {
+            let key = rsa_keys::public_key(public).ok_or(Error::InvalidKey)?;
+            match alg {
+                RS256 => rsa_verify::<Sha256>(key, false, message, signature),
+                RS384 => rsa_verify::<Sha384>(key, false, message, signature),
+                RS512 => rsa_verify::<Sha512>(key, false, message, signature),
+                PS256 => rsa_verify::<Sha256>(key, true, message, signature),
+                PS384 => rsa_verify::<Sha384>(key, true, message, signature),
+                _ => rsa_verify::<Sha512>(key, true, message, signature),
+            }
+        }
+        (ES256 | ES384 | ES512 | ES256K, Key::EC { curve, x, y, .. }) => {
+            let point: Vec<u8> = [0x04 /* uncompressed */]
+                .iter()
+                .chain(x.iter())
+                .chain(y.iter())
+                .copied()
+                .collect();
+            macro_rules! ecdsa_verify {
+                ($krate:ident) => {{
+                    let key = $krate::ecdsa::VerifyingKey::from_sec1_bytes(&point)
+                        .map_err(|_| Error::InvalidKey)?;
+                    let signature = $krate::ecdsa::Signature::from_slice(signature)
+                        .map_err(|_| Error::InvalidSignature)?;
+                    key.verify(message, &signature)
+                }};
+            }
+            match curve {
+                Curve::P256 => ecdsa_verify!(p256),
+                Curve::P384 => ecdsa_verify!(p384),
+                Curve::P521 => ecdsa_verify!(p521),
+                Curve::Secp256k1 => ecdsa_verify!(k256),
+            }
+            .map_err(|_| Error::InvalidSignature)
+        }
+        (EdDSA, Key::OKP { curve, x, .. }) => {
+            let key = ed25519_dalek::VerifyingKey::from_bytes(&ed25519_bytes(*curve, x)?)
+                .map_err(|_| Error::InvalidKey)?;
+            let signature = ed25519_dalek::Signature::from_slice(signature)
+                .map_err(|_| Error::InvalidSignature)?;
+            key.verify_strict(message, &signature)
+                .map_err(|_| Error::InvalidSignature)
+        }
+        _ => Err(Error::Key(crate::Error::MismatchedAlgorithm)),
+    }
+}
+
+fn ed25519_bytes(curve: OkpCurve, bytes: &ByteVec) -> Result<[u8; 32], Error> {
+    match curve {
+        OkpCurve::Ed25519 => bytes.as_ref().try_into().map_err(|_| Error::InvalidKey),
+        _ => Err(Error::UnsupportedCurve(curve)),
+    }
+}
+
+fn rsa_sign<D>(key: RsaPrivateKey, pss: bool, message: &[u8]) -> Result<Vec<u8>, Error>
+where
+    D: Digest + AssociatedOid + FixedOutputReset,
+{
+    let signature = if pss {
+        // The salt is as long as the digest, as required by RFC 7518 §3.5.
+        rsa::pss::BlindedSigningKey::<D>::new(key)
+            .try_sign_with_rng(&mut rand::thread_rng(), message)
+            .map(|signature| signature.to_vec())
+    } else {
+        rsa::pkcs1v15::SigningKey::<D>::new(key)
+            .try_sign(message)
+            .map(|signature| signature.to_vec())
+    };
+    signature.map_err(|_| Error::InvalidKey)
+}
+
+fn rsa_verify<D>(
+    key: RsaPublicKey,
+    pss: bool,
+    message: &[u8],
+    signature: &[u8],
+) -> Result<(), Error>
+where
+    D: Digest + AssociatedOid + FixedOutputReset,
+{
+    let result = if pss {
+        rsa::pss::Signature::try_from(signature)
+            .and_then(|signature| rsa::pss::VerifyingKey::<D>::new(key).verify(message, &signature))
+    } else {
+        rsa::pkcs1v15::Signature::try_from(signature).and_then(|signature| {
+            rsa::pkcs1v15::VerifyingKey::<D>::new(key).verify(message, &signature)
+        })
+    };
+    result.map_err(|_| Error::InvalidSignature)
+}
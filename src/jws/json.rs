@@ -0,0 +1,369 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::{algorithms, check_b64, check_critical, check_key, Error, Header};
+use crate::{
+    utils::{base64_decode, base64_encode},
+    JsonWebKey, JwkSet, KeyOps,
+};
+
+/// A JWS using the JSON serialization, as per
+/// [RFC 7515 §7.2](https://tools.ietf.org/html/rfc7515#section-7.2).
+///
+/// Both the general and the flattened syntax are accepted when deserializing.
+/// The general syntax is used when serializing, unless `to_flattened_string` is used.
+///
+/// ```
+/// # #[cfg(feature = "generate")] {
+/// use jsonwebkey::{jws, Algorithm, JsonWebKey, JwkSet, Key};
+///
+/// let alice = JsonWebKey::new(Key::generate_p256());
+/// let bob = JsonWebKey::new(Key::generate_ed25519());
+///
+/// let mut signed = jws::JwsJson::new(b"hello");
+/// signed.sign(&jws::Header::new(Algorithm::ES256), Default::default(), &alice).unwrap();
+/// signed.sign(&jws::Header::new(Algorithm::EdDSA), Default::default(), &bob).unwrap();
+///
+/// let keys = JwkSet::from(vec![alice, bob]);
+/// let signed: jws::JwsJson = signed.to_string().parse().unwrap();
+/// let verification = signed.verify(&keys).unwrap();
+/// assert!(verification.all_verified());
+/// assert_eq!(verification.payload, b"hello");
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "JwsJsonRepr", into = "JwsJsonRepr")]
+pub struct JwsJson {
+    /// The payload as it appears in the JSON: base64url-encoded, unless `encoded` is `false`.
+    /// `None` if the payload is detached.
+    payload: Option<String>,
+
+    /// Whether the signatures are over the base64url-encoded payload (i.e., `b64` isn't `false`).
+    encoded: bool,
+
+    signatures: Vec<Signature>,
+}
+
+/// One of the signatures of a `JwsJson`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Signature {
+    /// The base64url-encoded protected header.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protected: Option<String>,
+
+    /// The unprotected header.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub header: Option<Map<String, Value>>,
+
+    /// The base64url-encoded signature.
+    pub signature: String,
+}
+
+/// The members of both the general and the flattened syntax.
+#[derive(Serialize, Deserialize)]
+struct JwsJsonRepr {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    payload: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    signatures: Option<Vec<Signature>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    protected: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    header: Option<Map<String, Value>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    signature: Option<String>,
+}
+
+impl TryFrom<JwsJsonRepr> for JwsJson {
+    type Error = &'static str;
+
+    fn try_from(repr: JwsJsonRepr) -> Result<Self, Self::Error> {
+        let signatures = match (repr.signatures, repr.signature) {
+            (Some(signatures), None) if repr.protected.is_none() && repr.header.is_none() => {
+                signatures
+            }
+            (None, Some(signature)) => vec![Signature {
+                protected: repr.protected,
+                header: repr.header,
+                signature,
+            }],
+            _ => return Err("expected exactly one of `signatures` or `signature`"),
+        };
+        // The payload encoding must be the same for all signatures, so the first one decides.
+        // https://tools.ietf.org/html/rfc7797#section-7
+        let encoded = !signatures
+            .first()
+            .and_then(|signature| signature.protected_params().ok())
+            .is_some_and(|params| params.get("b64") == Some(&Value::Bool(false)));
+        Ok(Self {
+            payload: repr.payload,
+            encoded,
+            signatures,
+        })
+    }
+}
+
+impl From<JwsJson> for JwsJsonRepr {
+    fn from(jws: JwsJson) -> Self {
+        Self {
+            payload: jws.payload,
+            signatures: Some(jws.signatures),
+            protected: None,
+            header: None,
+            signature: None,
+        }
+    }
+}
+
+/// The outcome of verifying each of the signatures of a `JwsJson`.
+#[derive(Debug)]
+pub struct Verification<'a> {
+    pub payload: Vec<u8>,
+
+    /// The outcome for each signature, in the order that they appear in the JWS.
+    pub signatures: Vec<SignatureVerification<'a>>,
+}
+
+#[derive(Debug)]
+pub struct SignatureVerification<'a> {
+    /// The union of the protected and unprotected header, if it could be parsed.
+    pub header: Option<Header>,
+
+    /// The key that verified the signature, or the reason that no key did.
+    pub result: Result<&'a JsonWebKey, Error>,
+}
+
+impl<'a> Verification<'a> {
+    /// Returns whether there is at least one signature and every signature was verified.
+    pub fn all_verified(&self) -> bool {
+        !self.signatures.is_empty() && self.signatures.iter().all(|s| s.result.is_ok())
+    }
+
+    /// Returns the keys that verified a signature.
+    pub fn verified_keys(&self) -> impl Iterator<Item = &'a JsonWebKey> + '_ {
+        self.signatures
+            .iter()
+            .filter_map(|s| s.result.as_ref().ok().copied())
+    }
+}
+
+impl JwsJson {
+    /// Creates a JWS with no signatures.
+    pub fn new(payload: impl AsRef<[u8]>) -> Self {
+        Self {
+            payload: Some(base64_encode(payload)),
+            encoded: true,
+            signatures: Vec::new(),
+        }
+    }
+
+    /// Creates a JWS with no signatures whose payload is not base64url-encoded.
+    /// It must be signed using headers that have `b64` set to `false`.
+    pub fn new_unencoded(payload: impl Into<String>) -> Self {
+        Self {
+            payload: Some(payload.into()),
+            encoded: false,
+            signatures: Vec::new(),
+        }
+    }
+
+    pub fn signatures(&self) -> &[Signature] {
+        &self.signatures
+    }
+
+    /// Returns whether the payload is base64url-encoded, which is the default.
+    pub fn payload_encoded(&self) -> bool {
+        self.encoded
+    }
+
+    /// Returns the payload without verifying any signatures.
+    pub fn payload_unverified(&self) -> Result<Vec<u8>, Error> {
+        match &self.payload {
+            Some(payload) if self.encoded => base64_decode(payload).map_err(|_| Error::Malformed),
+            Some(payload) => Ok(payload.as_bytes().to_vec()),
+            None => Err(Error::DetachedPayload),
+        }
+    }
+
+    /// Removes the payload from the serialized JWS, as per
+    /// [RFC 7515 Appendix F](https://tools.ietf.org/html/rfc7515#appendix-F).
+    /// The signatures can still be verified using `verify_detached`.
+    pub fn detach_payload(&mut self) {
+        self.payload = None;
+    }
+
+    /// Adds a signature over the payload using `jwk`.
+    /// The `protected` and `unprotected` headers must not have parameters in common,
+    /// and `b64` and `crit` must be protected.
+    pub fn sign(
+        &mut self,
+        protected: &Header,
+        unprotected: Map<String, Value>,
+        jwk: &JsonWebKey,
+    ) -> Result<(), Error> {
+        let alg = protected.algorithm;
+        check_key(jwk, alg, KeyOps::SIGN)?;
+        check_b64(protected)?;
+        if protected.payload_encoded() != self.encoded {
+            return Err(Error::MismatchedPayloadEncoding);
+        }
+        let payload = self.payload.as_ref().ok_or(Error::DetachedPayload)?;
+        let protected = base64_encode(serde_json::to_vec(protected)?);
+        let signing_input = format!("{}.{}", protected, payload);
+        let mut signature = Signature {
+            protected: Some(protected),
+            header: Some(unprotected).filter(|header| !header.is_empty()),
+            signature: String::new(),
+        };
+        // Fails as verification would, e.g., if `b64` or `crit` is unprotected.
+        signature.joint_header()?;
+        signature.signature =
+            base64_encode(algorithms::sign(alg, &jwk.key, signing_input.as_bytes())?);
+        self.signatures.push(signature);
+        Ok(())
+    }
+
+    /// Verifies each signature using the best matching key from `keys`, as determined by
+    /// `JwkSet::select`. Only fails if the payload is malformed or detached; the outcome of
+    /// verifying the signatures is reported in the returned `Verification`.
+    pub fn verify<'a>(&self, keys: &'a JwkSet) -> Result<Verification<'a>, Error> {
+        let payload = self.payload_unverified()?;
+        let payload_segment = self.payload.as_deref().unwrap_or_default().as_bytes();
+        Ok(self.verify_signatures(payload, payload_segment, keys))
+    }
+
+    /// Like `verify`, but for a JWS whose payload is detached.
+    pub fn verify_detached<'a>(
+        &self,
+        payload: impl AsRef<[u8]>,
+        keys: &'a JwkSet,
+    ) -> Verification<'a> {
+        let payload = payload.as_ref();
+        if self.encoded {
+            let payload_segment = base64_encode(payload);
+            self.verify_signatures(payload.to_vec(), payload_segment.as_bytes(), keys)
+        } else {
+            self.verify_signatures(payload.to_vec(), payload, keys)
+        }
+    }
+
+    fn verify_signatures<'a>(
+        &self,
+        payload: Vec<u8>,
+        payload_segment: &[u8],
+        keys: &'a JwkSet,
+    ) -> Verification<'a> {
+        let signatures = self
+            .signatures
+            .iter()
+            .map(|signature| match signature.joint_header() {
+                Ok(header) => SignatureVerification {
+                    result: self.verify_signature(signature, &header, payload_segment, keys),
+                    header: Some(header),
+                },
+                Err(e) => SignatureVerification {
+                    header: None,
+                    result: Err(e),
+                },
+            })
+            .collect();
+        Verification {
+            payload,
+            signatures,
+        }
+    }
+
+    fn verify_signature<'a>(
+        &self,
+        signature: &Signature,
+        header: &Header,
+        payload_segment: &[u8],
+        keys: &'a JwkSet,
+    ) -> Result<&'a JsonWebKey, Error> {
+        if header.payload_encoded() != self.encoded {
+            return Err(Error::MismatchedPayloadEncoding);
+        }
+        let mut signing_input = signature.protected.clone().unwrap_or_default().into_bytes();
+        signing_input.push(b'.');
+        signing_input.extend_from_slice(payload_segment);
+        let signature_bytes = base64_decode(&signature.signature).map_err(|_| Error::Malformed)?;
+        let mut result = Err(Error::NoMatchingKey);
+        for jwk in keys.select(&header.key_header(), KeyOps::VERIFY) {
+            result = check_key(jwk, header.algorithm, KeyOps::VERIFY)
+                .and_then(|_| {
+                    algorithms::verify(header.algorithm, &jwk.key, &signing_input, &signature_bytes)
+                })
+                .map(|_| jwk);
+            if result.is_ok() {
+                break;
+            }
+        }
+        result
+    }
+
+    /// Returns the flattened JSON serialization, if this JWS has exactly one signature.
+    pub fn to_flattened_string(&self) -> Option<String> {
+        match self.signatures.as_slice() {
+            [signature] => Some(
+                serde_json::to_string(&JwsJsonRepr {
+                    payload: self.payload.clone(),
+                    signatures: None,
+                    protected: signature.protected.clone(),
+                    header: signature.header.clone(),
+                    signature: Some(signature.signature.clone()),
+                })
+                .unwrap(),
+            ),
+            _ => None,
+        }
+    }
+}
+
+impl Signature {
+    fn protected_params(&self) -> Result<Map<String, Value>, Error> {
+        Ok(match &self.protected {
+            Some(protected) => {
+                serde_json::from_slice(&base64_decode(protected).map_err(|_| Error::Malformed)?)?
+            }
+            None => Map::new(),
+        })
+    }
+
+    /// Returns the union of the protected and unprotected header parameters.
+    fn joint_header(&self) -> Result<Header, Error> {
+        let mut params = self.protected_params()?;
+        for (param, value) in self.header.iter().flatten() {
+            // https://tools.ietf.org/html/rfc7797#section-6
+            if param == "crit" || param == "b64" {
+                return Err(Error::InvalidB64Header);
+            }
+            if params.insert(param.clone(), value.clone()).is_some() {
+                return Err(Error::DuplicateHeaderParam(param.clone()));
+            }
+        }
+        let header = serde_json::from_value(Value::Object(params))?;
+        check_critical(&header)?;
+        Ok(header)
+    }
+}
+
+impl std::str::FromStr for JwsJson {
+    type Err = Error;
+    fn from_str(json: &str) -> Result<Self, Self::Err> {
+        Ok(serde_json::from_str(json)?)
+    }
+}
+
+impl std::fmt::Display for JwsJson {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if f.alternate() {
+            write!(f, "{}", serde_json::to_string_pretty(self).unwrap())
+        } else {
+            write!(f, "{}", serde_json::to_string(self).unwrap())
+        }
+    }
+}
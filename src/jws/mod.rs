@@ -0,0 +1,325 @@
+//! JSON Web Signatures, as per [RFC 7515](https://tools.ietf.org/html/rfc7515).
+//! The compact serialization is handled by `sign` and `verify`,
+//! and the JSON serialization by `JwsJson`.
+//! Detached and unencoded payloads ([RFC 7797](https://tools.ietf.org/html/rfc7797))
+//! are supported by both.
+//!
+//! ```
+//! # #[cfg(feature = "generate")] {
+//! use jsonwebkey::{jws, Algorithm, JsonWebKey, Key};
+//!
+//! let jwk = JsonWebKey::new(Key::generate_p256());
+//! let token = jws::sign(&jws::Header::new(Algorithm::ES256), b"hello", &jwk).unwrap();
+//! let verified = jws::verify(&token, &jwk).unwrap();
+//! assert_eq!(verified.payload, b"hello");
+//! # }
+//! ```
+
+pub(crate) mod algorithms;
+mod json;
+
+pub use json::{JwsJson, Signature, SignatureVerification, Verification};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    utils::{base64_decode, base64_encode},
+    Algorithm, JsonWebKey, KeyHeader, KeyOps, OkpCurve, PublicJwk,
+};
+
+/// The JOSE header of a JWS, as per [RFC 7515 §4](https://tools.ietf.org/html/rfc7515#section-4).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Header {
+    #[serde(rename = "alg")]
+    pub algorithm: Algorithm,
+
+    #[serde(default, rename = "kid", skip_serializing_if = "Option::is_none")]
+    pub key_id: Option<String>,
+
+    #[serde(default, rename = "typ", skip_serializing_if = "Option::is_none")]
+    pub token_type: Option<String>,
+
+    #[serde(default, rename = "cty", skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+
+    /// jwk: The public key that corresponds to the signing key.
+    /// This is a `PublicJwk` so that the signing key itself can't be embedded in the token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jwk: Option<Box<PublicJwk>>,
+
+    /// x5t: The SHA-1 thumbprint of the DER-encoded X.509 certificate of the signing key.
+    #[serde(default, rename = "x5t", skip_serializing_if = "Option::is_none")]
+    pub cert_thumbprint: Option<String>,
+
+    /// x5t#S256: The same data as the thumbprint, but digested using SHA-256
+    #[serde(default, rename = "x5t#S256", skip_serializing_if = "Option::is_none")]
+    pub cert_thumbprint_sha256: Option<String>,
+
+    /// b64: Whether the payload is base64url-encoded, as per
+    /// [RFC 7797](https://tools.ietf.org/html/rfc7797). Defaults to `true`.
+    /// When set, `b64` must also be listed in `critical`; see `set_unencoded_payload`.
+    #[serde(default, rename = "b64", skip_serializing_if = "Option::is_none")]
+    pub encode_payload: Option<bool>,
+
+    /// crit: The names of the extension parameters that must be understood by the recipient.
+    #[serde(default, rename = "crit", skip_serializing_if = "Vec::is_empty")]
+    pub critical: Vec<String>,
+
+    /// Any other header parameters.
+    #[serde(flatten)]
+    pub additional: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Header {
+    pub fn new(alg: Algorithm) -> Self {
+        Self {
+            algorithm: alg,
+            key_id: None,
+            token_type: None,
+            content_type: None,
+            jwk: None,
+            cert_thumbprint: None,
+            cert_thumbprint_sha256: None,
+            encode_payload: None,
+            critical: Vec::new(),
+            additional: Default::default(),
+        }
+    }
+
+    /// Sets `b64` to `false` and marks it as critical, so that the payload is signed as-is.
+    pub fn set_unencoded_payload(&mut self) {
+        self.encode_payload = Some(false);
+        if !self.critical.iter().any(|param| param == "b64") {
+            self.critical.push("b64".into());
+        }
+    }
+
+    /// Returns whether the payload is base64url-encoded, which is the default.
+    pub fn payload_encoded(&self) -> bool {
+        self.encode_payload != Some(false)
+    }
+
+    /// Returns the parameters used to select the verification key using `JwkSet::select`.
+    pub fn key_header(&self) -> KeyHeader {
+        KeyHeader {
+            algorithm: Some(self.algorithm.into()),
+            key_id: self.key_id.clone(),
+            cert_thumbprint: self.cert_thumbprint.clone(),
+            cert_thumbprint_sha256: self.cert_thumbprint_sha256.clone(),
+            jwk: self.jwk.clone().map(|jwk| Box::new(JsonWebKey::from(*jwk))),
+        }
+    }
+}
+
+/// The contents of a JWS whose signature has been verified.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Verified {
+    pub header: Header,
+    pub payload: Vec<u8>,
+}
+
+/// Signs `payload` using `jwk` and returns the JWS compact serialization.
+///
+/// If the header sets `b64` to `false`, the payload must be UTF-8 and must not contain periods,
+/// as per [RFC 7797 §5.2](https://tools.ietf.org/html/rfc7797#section-5.2).
+pub fn sign(header: &Header, payload: impl AsRef<[u8]>, jwk: &JsonWebKey) -> Result<String, Error> {
+    let payload = payload.as_ref();
+    let payload_segment = if header.payload_encoded() {
+        base64_encode(payload)
+    } else {
+        std::str::from_utf8(payload)
+            .ok()
+            .filter(|payload| !payload.contains('.'))
+            .ok_or(Error::InvalidUnencodedPayload)?
+            .to_string()
+    };
+    let (header, signature) = sign_parts(header, payload, jwk)?;
+    Ok(format!("{}.{}.{}", header, payload_segment, signature))
+}
+
+/// Signs `payload` using `jwk` and returns the JWS compact serialization without the payload,
+/// as per [RFC 7515 Appendix F](https://tools.ietf.org/html/rfc7515#appendix-F).
+/// The header may set `b64` to `false` to sign the payload as-is.
+pub fn sign_detached(
+    header: &Header,
+    payload: impl AsRef<[u8]>,
+    jwk: &JsonWebKey,
+) -> Result<String, Error> {
+    let (header, signature) = sign_parts(header, payload.as_ref(), jwk)?;
+    Ok(format!("{}..{}", header, signature))
+}
+
+/// Returns the encoded header and the encoded signature.
+fn sign_parts(
+    header: &Header,
+    payload: &[u8],
+    jwk: &JsonWebKey,
+) -> Result<(String, String), Error> {
+    check_key(jwk, header.algorithm, KeyOps::SIGN)?;
+    check_b64(header)?;
+    let encoded_header = base64_encode(serde_json::to_vec(header)?);
+    let signature = algorithms::sign(
+        header.algorithm,
+        &jwk.key,
+        &signing_input(&encoded_header, header, payload),
+    )?;
+    Ok((encoded_header, base64_encode(signature)))
+}
+
+/// https://tools.ietf.org/html/rfc7797#section-3
+fn signing_input(encoded_header: &str, header: &Header, payload: &[u8]) -> Vec<u8> {
+    let mut signing_input = format!("{}.", encoded_header).into_bytes();
+    if header.payload_encoded() {
+        signing_input.extend_from_slice(base64_encode(payload).as_bytes());
+    } else {
+        signing_input.extend_from_slice(payload);
+    }
+    signing_input
+}
+
+/// Decodes the header of a compact JWS without verifying it.
+/// This is useful for selecting the verification key from a `JwkSet`.
+pub fn decode_header(jws: &str) -> Result<Header, Error> {
+    let (header, _) = jws.split_once('.').ok_or(Error::Malformed)?;
+    parse_header(header)
+}
+
+/// Verifies a JWS in compact serialization using `jwk` and returns its header and payload.
+pub fn verify(jws: &str, jwk: &JsonWebKey) -> Result<Verified, Error> {
+    let (signing_input, signature) = jws.rsplit_once('.').ok_or(Error::Malformed)?;
+    let (encoded_header, payload) = signing_input.split_once('.').ok_or(Error::Malformed)?;
+    let header = parse_header(encoded_header)?;
+    let payload = if header.payload_encoded() {
+        base64_decode(payload).map_err(|_| Error::Malformed)?
+    } else if payload.contains('.') {
+        // https://tools.ietf.org/html/rfc7797#section-5.2
+        return Err(Error::InvalidUnencodedPayload);
+    } else {
+        payload.as_bytes().to_vec()
+    };
+    verify_parts(&header, encoded_header, &payload, signature, jwk)?;
+    Ok(Verified { header, payload })
+}
+
+/// Verifies a JWS in compact serialization whose payload is detached, and returns its header.
+pub fn verify_detached(
+    jws: &str,
+    payload: impl AsRef<[u8]>,
+    jwk: &JsonWebKey,
+) -> Result<Header, Error> {
+    let (encoded_header, signature) = jws.split_once("..").ok_or(Error::Malformed)?;
+    if signature.contains('.') {
+        return Err(Error::Malformed);
+    }
+    let header = parse_header(encoded_header)?;
+    verify_parts(&header, encoded_header, payload.as_ref(), signature, jwk)?;
+    Ok(header)
+}
+
+fn verify_parts(
+    header: &Header,
+    encoded_header: &str,
+    payload: &[u8],
+    signature: &str,
+    jwk: &JsonWebKey,
+) -> Result<(), Error> {
+    check_key(jwk, header.algorithm, KeyOps::VERIFY)?;
+    let signature = base64_decode(signature).map_err(|_| Error::Malformed)?;
+    algorithms::verify(
+        header.algorithm,
+        &jwk.key,
+        &signing_input(encoded_header, header, payload),
+        &signature,
+    )
+}
+
+fn parse_header(header: &str) -> Result<Header, Error> {
+    let header: Header =
+        serde_json::from_slice(&base64_decode(header).map_err(|_| Error::Malformed)?)?;
+    check_critical(&header)?;
+    Ok(header)
+}
+
+/// The extension header parameters that are understood when listed in `crit`.
+const UNDERSTOOD_CRITICAL_PARAMS: &[&str] = &["b64"];
+
+/// https://tools.ietf.org/html/rfc7515#section-4.1.11
+fn check_critical(header: &Header) -> Result<(), Error> {
+    if let Some(param) = header
+        .critical
+        .iter()
+        .find(|param| !UNDERSTOOD_CRITICAL_PARAMS.contains(&param.as_str()))
+    {
+        return Err(Error::UnsupportedCriticalParam(param.clone()));
+    }
+    check_b64(header)
+}
+
+/// https://tools.ietf.org/html/rfc7797#section-6
+fn check_b64(header: &Header) -> Result<(), Error> {
+    if header.encode_payload.is_some() != header.critical.iter().any(|param| param == "b64") {
+        return Err(Error::InvalidB64Header);
+    }
+    Ok(())
+}
+
+/// Checks that `jwk` may be used to perform `ops` using `alg`.
+pub(crate) fn check_key(jwk: &JsonWebKey, alg: Algorithm, ops: KeyOps) -> Result<(), Error> {
+    if !jwk.permits(&ops) {
+        return Err(Error::OperationNotPermitted(ops));
+    }
+    match jwk.algorithm {
+        Some(key_alg) if key_alg != alg.into() => Err(Error::AlgorithmNotPermitted(alg)),
+        _ => Ok(JsonWebKey::validate_algorithm(alg, &jwk.key)?),
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("malformed JWS")]
+    Malformed,
+
+    #[error("invalid JWS header: {0}")]
+    Header(#[from] serde_json::Error),
+
+    #[error("the `{0}` header parameter is both protected and unprotected")]
+    DuplicateHeaderParam(String),
+
+    #[error("unsupported critical header parameter: `{0}`")]
+    UnsupportedCriticalParam(String),
+
+    #[error("`b64` must be a protected header parameter that is listed in `crit`")]
+    InvalidB64Header,
+
+    #[error(
+        "an unencoded payload must be UTF-8 and, in the compact serialization, have no periods"
+    )]
+    InvalidUnencodedPayload,
+
+    #[error("the `b64` header parameter does not match the other signatures")]
+    MismatchedPayloadEncoding,
+
+    #[error("the payload is detached")]
+    DetachedPayload,
+
+    #[error("the key can not be used for {0:?}")]
+    OperationNotPermitted(KeyOps),
+
+    #[error("the key's `alg` does not permit {0:?}")]
+    AlgorithmNotPermitted(Algorithm),
+
+    #[error(transparent)]
+    Key(#[from] crate::Error),
+
+    #[error("invalid key")]
+    InvalidKey,
+
+    #[error("signatures using {0} keys are not supported")]
+    UnsupportedCurve(OkpCurve),
+
+    #[error("invalid signature")]
+    InvalidSignature,
+
+    #[error("no key matches the JWS header")]
+    NoMatchingKey,
+}
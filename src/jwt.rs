@@ -0,0 +1,368 @@
+//! JSON Web Tokens, as per [RFC 7519](https://tools.ietf.org/html/rfc7519), signed using `jws`.
+//!
+//! ```
+//! # #[cfg(feature = "generate")] {
+//! use jsonwebkey::{jws, jwt::{self, Clock}, Algorithm, JsonWebKey, Key};
+//!
+//! let jwk = JsonWebKey::new(Key::generate_p256());
+//! let claims: jwt::Claims = jwt::Claims {
+//!     subject: Some("alice".into()),
+//!     expires_at: Some(jwt::SystemClock.now() + 3600),
+//!     ..Default::default()
+//! };
+//! let token = jwt::encode(&jws::Header::new(Algorithm::ES256), &claims, &jwk).unwrap();
+//!
+//! let mut validation = jwt::Validation::new();
+//! validation.subject = Some("alice".into());
+//! let decoded: jwt::Token = jwt::decode(&token, &jwk, &validation).unwrap();
+//! assert_eq!(decoded.claims, claims);
+//! # }
+//! ```
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{jws, JsonWebKey};
+
+/// The claims of a JWT. The registered claims of
+/// [RFC 7519 §4.1](https://tools.ietf.org/html/rfc7519#section-4.1) are typed,
+/// and any other claims are (de)serialized from `custom`.
+///
+/// Times are `NumericDate`s: seconds since the Unix epoch. Fractional seconds are truncated.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Claims<T = serde_json::Map<String, serde_json::Value>> {
+    #[serde(default, rename = "iss", skip_serializing_if = "Option::is_none")]
+    pub issuer: Option<String>,
+
+    #[serde(default, rename = "sub", skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+
+    /// aud: The intended recipients. A single audience is (de)serialized as a string.
+    #[serde(
+        default,
+        rename = "aud",
+        with = "audience",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub audience: Vec<String>,
+
+    #[serde(
+        default,
+        rename = "exp",
+        with = "numeric_date",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub expires_at: Option<i64>,
+
+    #[serde(
+        default,
+        rename = "nbf",
+        with = "numeric_date",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub not_before: Option<i64>,
+
+    #[serde(
+        default,
+        rename = "iat",
+        with = "numeric_date",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub issued_at: Option<i64>,
+
+    #[serde(default, rename = "jti", skip_serializing_if = "Option::is_none")]
+    pub token_id: Option<String>,
+
+    /// Any other claims.
+    #[serde(flatten)]
+    pub custom: T,
+}
+
+impl<T> Claims<T> {
+    /// Returns whether the registered claim is present.
+    pub fn has(&self, claim: Claim) -> bool {
+        match claim {
+            Claim::Issuer => self.issuer.is_some(),
+            Claim::Subject => self.subject.is_some(),
+            Claim::Audience => !self.audience.is_empty(),
+            Claim::ExpiresAt => self.expires_at.is_some(),
+            Claim::NotBefore => self.not_before.is_some(),
+            Claim::IssuedAt => self.issued_at.is_some(),
+            Claim::TokenId => self.token_id.is_some(),
+        }
+    }
+}
+
+/// A registered claim name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Claim {
+    Issuer,
+    Subject,
+    Audience,
+    ExpiresAt,
+    NotBefore,
+    IssuedAt,
+    TokenId,
+}
+
+impl Claim {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Issuer => "iss",
+            Self::Subject => "sub",
+            Self::Audience => "aud",
+            Self::ExpiresAt => "exp",
+            Self::NotBefore => "nbf",
+            Self::IssuedAt => "iat",
+            Self::TokenId => "jti",
+        }
+    }
+}
+
+impl std::fmt::Display for Claim {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A source of the current time, in seconds since the Unix epoch.
+pub trait Clock {
+    fn now(&self) -> i64;
+}
+
+/// The system clock.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> i64 {
+        match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(elapsed) => elapsed.as_secs() as i64,
+            Err(err) => -(err.duration().as_secs() as i64),
+        }
+    }
+}
+
+impl<F: Fn() -> i64> Clock for F {
+    fn now(&self) -> i64 {
+        self()
+    }
+}
+
+/// How the claims of a JWT are validated by `decode`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Validation<C = SystemClock> {
+    /// The number of seconds of clock skew tolerated when checking `exp` and `nbf`.
+    /// Defaults to 60.
+    pub leeway: u64,
+
+    /// Whether to reject tokens whose `exp` has passed. Defaults to `true`.
+    pub validate_expires_at: bool,
+
+    /// Whether to reject tokens whose `nbf` hasn't yet come. Defaults to `true`.
+    pub validate_not_before: bool,
+
+    /// The claims that must be present. Defaults to `exp`.
+    pub required_claims: Vec<Claim>,
+
+    /// If set, the `iss` must be one of these.
+    pub issuers: Vec<String>,
+
+    /// The audiences that this recipient identifies with. When the token has an `aud`,
+    /// one of its values must be in this list, as per
+    /// [RFC 7519 §4.1.3](https://tools.ietf.org/html/rfc7519#section-4.1.3).
+    /// If set, a token without an `aud` is rejected.
+    pub audiences: Vec<String>,
+
+    /// If set, the `sub` must equal this.
+    pub subject: Option<String>,
+
+    /// The source of the current time.
+    pub clock: C,
+}
+
+impl Validation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Default for Validation {
+    fn default() -> Self {
+        Self {
+            leeway: 60,
+            validate_expires_at: true,
+            validate_not_before: true,
+            required_claims: vec![Claim::ExpiresAt],
+            issuers: Vec::new(),
+            audiences: Vec::new(),
+            subject: None,
+            clock: SystemClock,
+        }
+    }
+}
+
+impl<C: Clock> Validation<C> {
+    /// Replaces the source of the current time, e.g. with a closure returning a fixed time.
+    pub fn with_clock<C2: Clock>(self, clock: C2) -> Validation<C2> {
+        Validation {
+            leeway: self.leeway,
+            validate_expires_at: self.validate_expires_at,
+            validate_not_before: self.validate_not_before,
+            required_claims: self.required_claims,
+            issuers: self.issuers,
+            audiences: self.audiences,
+            subject: self.subject,
+            clock,
+        }
+    }
+
+    /// Checks the registered claims.
+    pub fn validate<T>(&self, claims: &Claims<T>) -> Result<(), Error> {
+        if let Some(claim) = self
+            .required_claims
+            .iter()
+            .find(|claim| !claims.has(**claim))
+        {
+            return Err(Error::MissingClaim(*claim));
+        }
+
+        let now = self.clock.now();
+        let leeway = self.leeway as i64;
+        match claims.expires_at {
+            Some(exp) if self.validate_expires_at && now >= exp.saturating_add(leeway) => {
+                return Err(Error::Expired(exp));
+            }
+            _ => {}
+        }
+        match claims.not_before {
+            Some(nbf) if self.validate_not_before && now < nbf.saturating_sub(leeway) => {
+                return Err(Error::NotYetValid(nbf));
+            }
+            _ => {}
+        }
+
+        if !self.issuers.is_empty()
+            && !claims
+                .issuer
+                .as_ref()
+                .is_some_and(|iss| self.issuers.contains(iss))
+        {
+            return Err(Error::InvalidIssuer);
+        }
+        if (!claims.audience.is_empty() || !self.audiences.is_empty())
+            && !claims
+                .audience
+                .iter()
+                .any(|aud| self.audiences.contains(aud))
+        {
+            return Err(Error::InvalidAudience);
+        }
+        if self.subject.is_some() && claims.subject != self.subject {
+            return Err(Error::InvalidSubject);
+        }
+        Ok(())
+    }
+}
+
+/// A JWT whose signature and claims have been validated.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Token<T = serde_json::Map<String, serde_json::Value>> {
+    pub header: jws::Header,
+    pub claims: Claims<T>,
+}
+
+/// Signs the claims using `jwk` and returns the JWT. The `typ` defaults to `JWT`.
+pub fn encode<T: Serialize>(
+    header: &jws::Header,
+    claims: &Claims<T>,
+    jwk: &JsonWebKey,
+) -> Result<String, Error> {
+    let mut header = header.clone();
+    header.token_type.get_or_insert_with(|| "JWT".into());
+    let claims = serde_json::to_vec(claims).map_err(Error::Claims)?;
+    Ok(jws::sign(&header, claims, jwk)?)
+}
+
+/// Verifies the JWT using `jwk`, and validates its claims.
+pub fn decode<T: DeserializeOwned, C: Clock>(
+    token: &str,
+    jwk: &JsonWebKey,
+    validation: &Validation<C>,
+) -> Result<Token<T>, Error> {
+    let verified = jws::verify(token, jwk)?;
+    let claims: Claims<T> = serde_json::from_slice(&verified.payload).map_err(Error::Claims)?;
+    validation.validate(&claims)?;
+    Ok(Token {
+        header: verified.header,
+        claims,
+    })
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Jws(#[from] jws::Error),
+
+    #[error("invalid JWT claims: {0}")]
+    Claims(serde_json::Error),
+
+    #[error("missing the `{0}` claim")]
+    MissingClaim(Claim),
+
+    #[error("the token expired at {0}")]
+    Expired(i64),
+
+    #[error("the token is not valid before {0}")]
+    NotYetValid(i64),
+
+    #[error("the token has an unexpected issuer")]
+    InvalidIssuer,
+
+    #[error("the token is not intended for this audience")]
+    InvalidAudience,
+
+    #[error("the token has an unexpected subject")]
+    InvalidSubject,
+}
+
+mod audience {
+    use super::*;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Audience {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    pub(super) fn serialize<S: Serializer>(audience: &[String], s: S) -> Result<S::Ok, S::Error> {
+        match audience {
+            [audience] => audience.serialize(s),
+            _ => audience.serialize(s),
+        }
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<String>, D::Error> {
+        Ok(match Audience::deserialize(d)? {
+            Audience::One(audience) => vec![audience],
+            Audience::Many(audiences) => audiences,
+        })
+    }
+}
+
+mod numeric_date {
+    use super::*;
+
+    pub(super) fn serialize<S: Serializer>(date: &Option<i64>, s: S) -> Result<S::Ok, S::Error> {
+        date.serialize(s)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<i64>, D::Error> {
+        Ok(Option::<serde_json::Number>::deserialize(d)?.map(|date| {
+            date.as_i64()
+                .unwrap_or(date.as_f64().unwrap_or_default() as i64)
+        }))
+    }
+}
@@ -1,43 +1,75 @@
+use std::{borrow::Borrow, collections::BTreeSet};
+
 use serde::{
-    de::{self, Deserialize, Deserializer},
+    de::{Deserialize, Deserializer},
     ser::{Serialize, SerializeSeq, Serializer},
 };
 
+/// A set of key operations, as per [RFC 7517 §4.3](https://tools.ietf.org/html/rfc7517#section-4.3).
+///
+/// Operations other than the registered ones are kept by name so that they round-trip,
+/// but are never required by this crate.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct KeyOps {
+    flags: Flags,
+    other: BTreeSet<String>,
+}
+
 macro_rules! impl_key_ops {
-    ($(($key_op:ident, $const_name:ident, $i:literal)),+,) => {
+    ($(($key_op:ident, $variant:ident, $const_name:ident, $i:literal)),+,) => {
         bitflags::bitflags! {
             #[derive(Default)]
-            pub struct KeyOps: u16 {
+            struct Flags: u16 {
                 $(const $const_name = $i;)*
             }
         }
 
-        impl Serialize for KeyOps {
-            fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
-                let mut seq = s.serialize_seq(Some(self.bits().count_ones() as usize))?;
-                $(
-                    if self.contains(KeyOps::$const_name) {
-                        seq.serialize_element(stringify!($key_op))?;
-                    }
-                )+
-                seq.end()
+        /// A single key operation.
+        #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+        pub enum KeyOp {
+            $($variant,)*
+            /// An operation that isn't registered.
+            Other(String),
+        }
+
+        impl KeyOp {
+            pub fn name(&self) -> &str {
+                match self {
+                    $(Self::$variant => stringify!($key_op),)*
+                    Self::Other(name) => name,
+                }
+            }
+
+            fn flag(&self) -> Option<Flags> {
+                match self {
+                    $(Self::$variant => Some(Flags::$const_name),)*
+                    Self::Other(_) => None,
+                }
             }
         }
 
-        impl<'de> Deserialize<'de> for KeyOps {
-            fn deserialize<D: Deserializer<'de>>(d: D) -> Result<KeyOps, D::Error> {
-                let op_strs: Vec<String> = Deserialize::deserialize(d)?;
-                let mut ops = KeyOps::default();
-                for op_str in op_strs {
-                    $(
-                        if op_str == stringify!($key_op) {
-                            ops |= KeyOps::$const_name;
-                            continue;
-                        }
-                    )+
-                        return Err(de::Error::custom(&format!("invalid key op: `{}`", op_str)));
+        impl From<&str> for KeyOp {
+            fn from(name: &str) -> Self {
+                match name {
+                    $(stringify!($key_op) => Self::$variant,)*
+                    _ => Self::Other(name.into()),
                 }
-                Ok(ops)
+            }
+        }
+
+        impl KeyOps {
+            $(pub const $const_name: Self = Self {
+                flags: Flags::$const_name,
+                other: BTreeSet::new(),
+            };)*
+
+            /// Returns the operations in the set: first the registered ones, in the order of
+            /// RFC 7517, then the others, by name.
+            pub fn iter(&self) -> impl Iterator<Item = KeyOp> + '_ {
+                [$(KeyOp::$variant),*]
+                    .into_iter()
+                    .filter(|op| self.flags.contains(op.flag().unwrap()))
+                    .chain(self.other.iter().map(|name| KeyOp::Other(name.clone())))
             }
         }
     };
@@ -45,24 +77,140 @@ macro_rules! impl_key_ops {
 
 #[rustfmt::skip]
 impl_key_ops!(
-    (sign,       SIGN,        0b00000001),
-    (verify,     VERIFY,      0b00000010),
-    (encrypt,    ENCRYPT,     0b00000100),
-    (decrypt,    DECRYPT,     0b00001000),
-    (wrapKey,    WRAP_KEY,    0b00010000),
-    (unwrapKey,  UNWRAP_KEY,  0b00100000),
-    (deriveKey,  DERIVE_KEY,  0b01000000),
-    (deriveBits, DERIVE_BITS, 0b10000000),
+    (sign,       Sign,       SIGN,        0b00000001),
+    (verify,     Verify,     VERIFY,      0b00000010),
+    (encrypt,    Encrypt,    ENCRYPT,     0b00000100),
+    (decrypt,    Decrypt,    DECRYPT,     0b00001000),
+    (wrapKey,    WrapKey,    WRAP_KEY,    0b00010000),
+    (unwrapKey,  UnwrapKey,  UNWRAP_KEY,  0b00100000),
+    (deriveKey,  DeriveKey,  DERIVE_KEY,  0b01000000),
+    (deriveBits, DeriveBits, DERIVE_BITS, 0b10000000),
 );
 
+impl KeyOps {
+    pub const fn empty() -> Self {
+        Self {
+            flags: Flags::empty(),
+            other: BTreeSet::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.flags.is_empty() && self.other.is_empty()
+    }
+
+    /// Returns whether all of the operations in `other` are in this set.
+    pub fn contains(&self, other: impl Borrow<Self>) -> bool {
+        let other = other.borrow();
+        self.flags.contains(other.flags) && other.other.is_subset(&self.other)
+    }
+
+    /// Returns whether any of the operations in `other` are in this set.
+    pub fn intersects(&self, other: impl Borrow<Self>) -> bool {
+        let other = other.borrow();
+        self.flags.intersects(other.flags) || !self.other.is_disjoint(&other.other)
+    }
+
+    pub fn insert(&mut self, op: KeyOp) {
+        match op {
+            KeyOp::Other(name) => {
+                self.other.insert(name);
+            }
+            op => self.flags.insert(op.flag().unwrap()),
+        }
+    }
+
+    pub fn remove(&mut self, op: &KeyOp) {
+        match op {
+            KeyOp::Other(name) => {
+                self.other.remove(name);
+            }
+            op => self.flags.remove(op.flag().unwrap()),
+        }
+    }
+
+    /// Returns the names of the operations that aren't registered.
+    pub fn other(&self) -> impl Iterator<Item = &str> {
+        self.other.iter().map(String::as_str)
+    }
+}
+
+impl From<KeyOp> for KeyOps {
+    fn from(op: KeyOp) -> Self {
+        let mut ops = Self::empty();
+        ops.insert(op);
+        ops
+    }
+}
+
+impl FromIterator<KeyOp> for KeyOps {
+    fn from_iter<I: IntoIterator<Item = KeyOp>>(iter: I) -> Self {
+        let mut ops = Self::empty();
+        for op in iter {
+            ops.insert(op);
+        }
+        ops
+    }
+}
+
+impl std::ops::BitOr for KeyOps {
+    type Output = Self;
+
+    fn bitor(mut self, rhs: Self) -> Self {
+        self |= rhs;
+        self
+    }
+}
+
+impl std::ops::BitOrAssign for KeyOps {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.flags |= rhs.flags;
+        self.other.extend(rhs.other);
+    }
+}
+
+impl std::ops::BitAnd for KeyOps {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        Self {
+            flags: self.flags & rhs.flags,
+            other: self.other.intersection(&rhs.other).cloned().collect(),
+        }
+    }
+}
+
+impl Serialize for KeyOps {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        let len = self.flags.bits().count_ones() as usize + self.other.len();
+        let mut seq = s.serialize_seq(Some(len))?;
+        for op in self.iter() {
+            seq.serialize_element(op.name())?;
+        }
+        seq.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for KeyOps {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<KeyOps, D::Error> {
+        let op_strs: Vec<String> = Deserialize::deserialize(d)?;
+        Ok(op_strs.iter().map(|op| KeyOp::from(op.as_str())).collect())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
-    fn deserialize_invalid() {
-        let result: Result<KeyOps, _> = serde_json::from_str(r#"["unknown"]"#);
-        assert!(result.is_err());
+    fn deserialize_other() {
+        let ops: KeyOps = serde_json::from_str(r#"["x-unknown","sign"]"#).unwrap();
+        assert_eq!(ops, KeyOps::SIGN | KeyOp::Other("x-unknown".into()).into());
+        assert_eq!(ops.other().collect::<Vec<_>>(), ["x-unknown"]);
+        assert_eq!(
+            serde_json::to_string(&ops).unwrap(),
+            r#"["sign","x-unknown"]"#
+        );
     }
 
     #[test]
@@ -0,0 +1,128 @@
+use std::cmp::Reverse;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{JsonWebKey, JwkSet, KeyAlgorithm, KeyOps, KeyUse};
+
+/// The JWS/JWE header parameters that identify the key used to protect a token, as per
+/// [RFC 7515 §4.1](https://tools.ietf.org/html/rfc7515#section-4.1).
+/// Other header parameters are ignored when deserializing.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyHeader {
+    #[serde(default, rename = "alg", skip_serializing_if = "Option::is_none")]
+    pub algorithm: Option<KeyAlgorithm>,
+
+    #[serde(default, rename = "kid", skip_serializing_if = "Option::is_none")]
+    pub key_id: Option<String>,
+
+    /// x5t: The SHA-1 thumbprint of the DER-encoded X.509 certificate of the key.
+    #[serde(default, rename = "x5t", skip_serializing_if = "Option::is_none")]
+    pub cert_thumbprint: Option<String>,
+
+    /// x5t#S256: The same data as the thumbprint, but digested using SHA-256
+    #[serde(default, rename = "x5t#S256", skip_serializing_if = "Option::is_none")]
+    pub cert_thumbprint_sha256: Option<String>,
+
+    /// jwk: The public key used to protect the token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jwk: Option<Box<JsonWebKey>>,
+}
+
+/// How well a candidate key matches a `KeyHeader`. Fields are in order of importance.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Rank {
+    key_id: bool,
+    key_material: bool,
+    algorithm: bool,
+}
+
+impl JsonWebKey {
+    /// Returns whether this key's `use` and `key_ops`, when present, permit all of `ops`,
+    /// and whether this key has the private components that `ops` require.
+    pub(crate) fn permits(&self, ops: &KeyOps) -> bool {
+        let signing_ops = KeyOps::SIGN | KeyOps::VERIFY;
+        let private_ops = KeyOps::SIGN
+            | KeyOps::DECRYPT
+            | KeyOps::UNWRAP_KEY
+            | KeyOps::DERIVE_KEY
+            | KeyOps::DERIVE_BITS;
+        let use_permits = match &self.key_use {
+            Some(KeyUse::Signing) => signing_ops.contains(ops),
+            Some(KeyUse::Encryption) => !ops.intersects(signing_ops),
+            Some(KeyUse::Other(_)) => false,
+            None => true,
+        };
+        use_permits
+            && (self.key_ops.is_empty() || self.key_ops.contains(ops))
+            && (!ops.intersects(private_ops) || self.key.is_private())
+    }
+
+    fn rank(&self, header: &KeyHeader, ops: &KeyOps) -> Option<Rank> {
+        if !self.permits(ops) {
+            return None;
+        }
+
+        if let Some(alg) = header.algorithm {
+            if self.algorithm.is_some_and(|key_alg| key_alg != alg)
+                || Self::validate_key_algorithm(alg, &self.key).is_err()
+            {
+                return None;
+            }
+        }
+
+        // Returns `None` if the values contradict each other, and whether they're equal otherwise.
+        fn compare<T: PartialEq + ?Sized>(header: Option<&T>, key: Option<&T>) -> Option<bool> {
+            match (header, key) {
+                (Some(header), Some(key)) if header != key => None,
+                (Some(_), Some(_)) => Some(true),
+                _ => Some(false),
+            }
+        }
+
+        let key_id = compare(header.key_id.as_deref(), self.key_id.as_deref())?;
+        let cert_thumbprint = compare(
+            header.cert_thumbprint.as_deref(),
+            self.x5.thumbprint.as_deref(),
+        )?;
+        let cert_thumbprint_sha256 = compare(
+            header.cert_thumbprint_sha256.as_deref(),
+            self.x5.thumbprint_sha256.as_deref(),
+        )?;
+        let jwk = match &header.jwk {
+            Some(jwk) => match (jwk.key.to_public(), self.key.to_public()) {
+                (Some(header_key), Some(key)) if header_key == key => true,
+                _ => return None,
+            },
+            None => false,
+        };
+
+        Some(Rank {
+            key_id,
+            key_material: jwk || cert_thumbprint || cert_thumbprint_sha256,
+            algorithm: header.algorithm.is_some() && self.algorithm.is_some(),
+        })
+    }
+}
+
+impl JwkSet {
+    /// Returns the keys that can perform `ops` (e.g., `KeyOps::VERIFY`) for a token having the
+    /// provided header, best match first.
+    ///
+    /// A key is a candidate if
+    /// * its `use` and `key_ops` permit `ops`, and it's private if `ops` require it,
+    /// * its key type is suitable for the header's `alg`, and its own `alg` (if any) is the same,
+    /// * its `kid`, `x5t`, and `x5t#S256` (if any) equal the header's, and
+    /// * its public key is the header's `jwk`, if there is one.
+    ///
+    /// Candidates that match the header's `kid` rank highest, followed by those whose key
+    /// material matches the `jwk`, `x5t`, or `x5t#S256`, followed by those with an explicit `alg`.
+    /// Otherwise, keys are returned in the order they appear in the set.
+    pub fn select(&self, header: &KeyHeader, ops: KeyOps) -> Vec<&JsonWebKey> {
+        let mut candidates: Vec<_> = self
+            .keys()
+            .filter_map(|jwk| Some((jwk.rank(header, &ops)?, jwk)))
+            .collect();
+        candidates.sort_by_key(|(rank, _)| Reverse(*rank));
+        candidates.into_iter().map(|(_, jwk)| jwk).collect()
+    }
+}
@@ -0,0 +1,107 @@
+use crate::{Error, JsonWebKey, Key, KeyOp, KeyOps, KeyUse, OkpCurve};
+
+impl JsonWebKey {
+    /// Sets `use`, failing if it contradicts `key_ops`.
+    pub fn set_key_use(&mut self, key_use: KeyUse) -> Result<(), Error> {
+        if let Some(err) = usage_errors(Some(&key_use), &self.key_ops, &self.key)
+            .into_iter()
+            .next()
+        {
+            return Err(err.into());
+        }
+        self.key_use = Some(key_use);
+        Ok(())
+    }
+
+    /// Sets `key_ops`, failing if they contradict `use` or aren't possible with the key.
+    pub fn set_key_ops(&mut self, key_ops: KeyOps) -> Result<(), Error> {
+        if let Some(err) = usage_errors(self.key_use.as_ref(), &key_ops, &self.key)
+            .into_iter()
+            .next()
+        {
+            return Err(err.into());
+        }
+        self.key_ops = key_ops;
+        Ok(())
+    }
+
+    /// Checks `use` and `key_ops` as per [RFC 7517 §4.3](https://tools.ietf.org/html/rfc7517#section-4.3):
+    /// * `sign` and `verify` are only consistent with a `use` of `sig`, and the other registered
+    ///   operations only with `enc`,
+    /// * `sign`, `decrypt`, `unwrapKey`, `deriveKey`, and `deriveBits` require a private key,
+    /// * RSA keys can't derive, Ed25519 and Ed448 keys can only sign and verify, and
+    ///   X25519 and X448 keys can't.
+    ///
+    /// Unregistered uses and operations are not checked.
+    pub fn check_key_usage(&self) -> Result<(), KeyUsageError> {
+        match self.key_usage_errors().into_iter().next() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns every problem that `check_key_usage` would report, in the order of `key_ops`.
+    pub fn key_usage_errors(&self) -> Vec<KeyUsageError> {
+        usage_errors(self.key_use.as_ref(), &self.key_ops, &self.key)
+    }
+}
+
+fn usage_errors(key_use: Option<&KeyUse>, key_ops: &KeyOps, key: &Key) -> Vec<KeyUsageError> {
+    let mut errors = Vec::new();
+    for op in key_ops.iter() {
+        let op_use = match op {
+            KeyOp::Sign | KeyOp::Verify => KeyUse::Signing,
+            KeyOp::Other(_) => continue,
+            _ => KeyUse::Encryption,
+        };
+        if let Some(key_use @ (KeyUse::Signing | KeyUse::Encryption)) = key_use {
+            if *key_use != op_use {
+                errors.push(KeyUsageError::ContradictsKeyUse {
+                    key_use: key_use.clone(),
+                    op: op.clone(),
+                });
+            }
+        }
+        if !key_supports(key, &op) {
+            errors.push(KeyUsageError::UnsupportedByKey(op));
+        } else if requires_private_key(&op) && !key.is_private() {
+            errors.push(KeyUsageError::PrivateKeyRequired(op));
+        }
+    }
+    errors
+}
+
+fn requires_private_key(op: &KeyOp) -> bool {
+    matches!(
+        op,
+        KeyOp::Sign | KeyOp::Decrypt | KeyOp::UnwrapKey | KeyOp::DeriveKey | KeyOp::DeriveBits
+    )
+}
+
+fn key_supports(key: &Key, op: &KeyOp) -> bool {
+    let signing = matches!(op, KeyOp::Sign | KeyOp::Verify);
+    match key {
+        Key::RSA { .. } => !matches!(op, KeyOp::DeriveKey | KeyOp::DeriveBits),
+        Key::OKP {
+            curve: OkpCurve::Ed25519 | OkpCurve::Ed448,
+            ..
+        } => signing,
+        Key::OKP {
+            curve: OkpCurve::X25519 | OkpCurve::X448,
+            ..
+        } => !signing,
+        Key::EC { .. } | Key::Symmetric { .. } => true,
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum KeyUsageError {
+    #[error("the `{}` key operation contradicts a `use` of `{}`", .op.name(), .key_use.name())]
+    ContradictsKeyUse { key_use: KeyUse, op: KeyOp },
+
+    #[error("the `{}` key operation requires a private key", .0.name())]
+    PrivateKeyRequired(KeyOp),
+
+    #[error("the `{}` key operation is not possible with the key type", .0.name())]
+    UnsupportedByKey(KeyOp),
+}
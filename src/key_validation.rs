@@ -0,0 +1,192 @@
+use num_bigint::BigUint;
+
+use crate::{ByteVec, Curve, Key, OkpCurve, RsaPrivate, RsaPublic};
+
+impl Key {
+    /// Checks that the key is mathematically consistent, which deserialization does not:
+    /// * EC and OKP public keys must be valid points other than the identity
+    ///   (or, for Ed25519, another point of small order),
+    /// * EC and OKP private keys must be in range and correspond to the public key,
+    /// * RSA keys must have an odd modulus and public exponent, `d` must invert `e`, and,
+    ///   when present, `p * q` must equal `n` and `dp`, `dq`, and `qi` must be consistent.
+    ///
+    /// Ed448 and X448 keys are only checked for their lengths.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        match self {
+            Self::EC { curve, d, x, y } => validate_ec(*curve, d.as_ref(), x, y),
+            Self::RSA { public, private } => validate_rsa(public, private.as_ref()),
+            Self::Symmetric { key } if key.is_empty() => Err(ValidationError::EmptySymmetricKey),
+            Self::Symmetric { .. } => Ok(()),
+            Self::OKP { curve, d, x } => validate_okp(*curve, d.as_ref(), x),
+        }
+    }
+}
+
+fn validate_ec(
+    curve: Curve,
+    d: Option<&ByteVec>,
+    x: &ByteVec,
+    y: &ByteVec,
+) -> Result<(), ValidationError> {
+    let len = curve.coordinate_len();
+    if x.len() != len || y.len() != len || d.is_some_and(|d| d.len() != len) {
+        return Err(ValidationError::InvalidLength);
+    }
+    if x.iter().chain(y.iter()).all(|&b| b == 0) {
+        return Err(ValidationError::IdentityPoint);
+    }
+    let point: Vec<u8> = [0x04 /* uncompressed */]
+        .iter()
+        .chain(x.iter())
+        .chain(y.iter())
+        .copied()
+        .collect();
+    macro_rules! validate {
+        ($krate:ident) => {{
+            let public_key = $krate::PublicKey::from_sec1_bytes(&point)
+                .map_err(|_| ValidationError::PointNotOnCurve)?;
+            if let Some(d) = d {
+                let secret_key = $krate::SecretKey::from_slice(d)
+                    .map_err(|_| ValidationError::PrivateKeyOutOfRange)?;
+                if secret_key.public_key() != public_key {
+                    return Err(ValidationError::MismatchedPrivateKey);
+                }
+            }
+            Ok(())
+        }};
+    }
+    match curve {
+        Curve::P256 => validate!(p256),
+        Curve::P384 => validate!(p384),
+        Curve::P521 => validate!(p521),
+        Curve::Secp256k1 => validate!(k256),
+    }
+}
+
+fn validate_okp(curve: OkpCurve, d: Option<&ByteVec>, x: &ByteVec) -> Result<(), ValidationError> {
+    if x.len() != curve.public_key_len() || d.is_some_and(|d| d.len() != curve.private_key_len()) {
+        return Err(ValidationError::InvalidLength);
+    }
+    match curve {
+        OkpCurve::Ed25519 => {
+            let x: [u8; 32] = x.as_ref().try_into().unwrap();
+            let public_key = ed25519_dalek::VerifyingKey::from_bytes(&x)
+                .map_err(|_| ValidationError::PointNotOnCurve)?;
+            if public_key.is_weak() {
+                return Err(ValidationError::IdentityPoint);
+            }
+            if let Some(d) = d {
+                let d: [u8; 32] = d.as_ref().try_into().unwrap();
+                if ed25519_dalek::SigningKey::from_bytes(&d).verifying_key() != public_key {
+                    return Err(ValidationError::MismatchedPrivateKey);
+                }
+            }
+            Ok(())
+        }
+        OkpCurve::X25519 => {
+            let x: [u8; 32] = x.as_ref().try_into().unwrap();
+            if x == [0; 32] {
+                return Err(ValidationError::IdentityPoint);
+            }
+            if let Some(d) = d {
+                let d: [u8; 32] = d.as_ref().try_into().unwrap();
+                let secret = x25519_dalek::StaticSecret::from(d);
+                if x25519_dalek::PublicKey::from(&secret).as_bytes() != &x {
+                    return Err(ValidationError::MismatchedPrivateKey);
+                }
+            }
+            Ok(())
+        }
+        OkpCurve::Ed448 | OkpCurve::X448 => Ok(()),
+    }
+}
+
+fn validate_rsa(public: &RsaPublic, private: Option<&RsaPrivate>) -> Result<(), ValidationError> {
+    let one = BigUint::from(1u8);
+    let n = BigUint::from_bytes_be(&public.n);
+    let e = BigUint::from_bytes_be(&public.e);
+    if n <= one || !n.bit(0) {
+        return Err(ValidationError::InvalidRsaModulus);
+    }
+    if e <= one || !e.bit(0) || e >= n {
+        return Err(ValidationError::InvalidRsaExponent);
+    }
+    let private = match private {
+        Some(private) => private,
+        None => return Ok(()),
+    };
+
+    // `d` inverts `e` iff encrypting then decrypting is the identity.
+    let d = BigUint::from_bytes_be(&private.d);
+    let message = BigUint::from(2u8);
+    if message.modpow(&e, &n).modpow(&d, &n) != message {
+        return Err(ValidationError::MismatchedPrivateKey);
+    }
+
+    let crt_params = [
+        &private.p,
+        &private.q,
+        &private.dp,
+        &private.dq,
+        &private.qi,
+    ];
+    if crt_params.iter().all(|param| param.is_none()) {
+        return Ok(());
+    }
+    let [p, q, dp, dq, qi] = match crt_params {
+        [Some(p), Some(q), Some(dp), Some(dq), Some(qi)] => {
+            [p, q, dp, dq, qi].map(|param| BigUint::from_bytes_be(param))
+        }
+        // https://tools.ietf.org/html/rfc7518#section-6.3.2
+        _ => return Err(ValidationError::IncompleteRsaCrtParams),
+    };
+    if p <= one || q <= one || &p * &q != n {
+        return Err(ValidationError::RsaModulusMismatch);
+    }
+    if dp != &d % (&p - 1u8) {
+        return Err(ValidationError::InconsistentRsaCrtParam("dp"));
+    }
+    if dq != &d % (&q - 1u8) {
+        return Err(ValidationError::InconsistentRsaCrtParam("dq"));
+    }
+    if qi >= p || (&qi * &q) % &p != one {
+        return Err(ValidationError::InconsistentRsaCrtParam("qi"));
+    }
+    Ok(())
+}
+
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum ValidationError {
+    #[error("the key parameters have the wrong length for the curve")]
+    InvalidLength,
+
+    #[error("the public key is not a point on the curve")]
+    PointNotOnCurve,
+
+    #[error("the public key is the identity or has small order")]
+    IdentityPoint,
+
+    #[error("the private key is zero or not less than the order of the curve")]
+    PrivateKeyOutOfRange,
+
+    #[error("the private key does not correspond to the public key")]
+    MismatchedPrivateKey,
+
+    #[error("the RSA modulus must be odd")]
+    InvalidRsaModulus,
+
+    #[error("the RSA public exponent must be odd, greater than 1, and less than the modulus")]
+    InvalidRsaExponent,
+
+    #[error("the product of the RSA primes is not the modulus")]
+    RsaModulusMismatch,
+
+    #[error("if any of p, q, dp, dq, qi are specified, all must be")]
+    IncompleteRsaCrtParams,
+
+    #[error("the RSA CRT parameter `{0}` is inconsistent with the other parameters")]
+    InconsistentRsaCrtParam(&'static str),
+
+    #[error("the symmetric key is empty")]
+    EmptySymmetricKey,
+}
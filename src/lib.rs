@@ -3,9 +3,6 @@
 
 //! *[JSON Web Key (JWK)](https://tools.ietf.org/html/rfc7517#section-4.3) (de)serialization, generation, and conversion.*
 //!
-//! **Note**: this crate requires Rust nightly >= 1.45 because it uses
-//! `feature(const_generics, fixed_size_array)` to enable statically-checked key lengths.
-//!
 //! ## Examples
 //!
 //! ### Deserializing from JSON
@@ -17,7 +14,7 @@
 //!    "kty": "oct",
 //!    "use": "sig",
 //!    "kid": "my signing key",
-//!    "k": "Wpj30SfkzM_m0Sa_B2NqNw",
+//!    "k": "Wpj30SfkzM_m0Sa_B2NqNwAVU8Yi3NoQa5jfLHtTPRs",
 //!    "alg": "HS256"
 //! }"#;
 //! let the_jwk: jwk::JsonWebKey = jwt_str.parse().unwrap();
@@ -39,7 +36,7 @@
 //! let mut my_jwk = jwk::JsonWebKey::new(jwk::Key::generate_p256());
 //! my_jwk.set_algorithm(jwk::Algorithm::ES256);
 //!
-//! let alg: jwt::Algorithm = my_jwk.algorithm.unwrap().into();
+//! let alg: jwt::Algorithm = my_jwk.algorithm.unwrap().try_into().unwrap();
 //! let token = jwt::encode(
 //!     &jwt::Header::new(alg),
 //!     &TokenClaims {
@@ -56,27 +53,70 @@
 //!
 //! ## Features
 //!
-//! * `convert` - enables `Key::{to_der, to_pem}`.
-//!               This pulls in the [yasna](https://crates.io/crates/yasna) crate.
-//! * `generate` - enables `Key::{generate_p256, generate_symmetric}`.
-//!                This pulls in the [p256](https://crates.io/crates/p256) and [rand](https://crates.io/crates/rand) crates.
-//! * `jsonwebtoken` - enables conversions to types in the [jsonwebtoken](https://crates.io/crates/jsonwebtoken) crate.
+//! * `pkcs-convert` - enables `Key::{to_der, to_pem, from_der, from_pem, from_rsa_primes}` and
+//!   `RsaPrivate::complete`.
+//!   This pulls in the [yasna](https://crates.io/crates/yasna) and
+//!   [num-bigint](https://crates.io/crates/num-bigint) crates.
+//! * `generate` - enables `Key::{generate_p256, generate_secp256k1, generate_ed25519,
+//!   generate_x25519, generate_rsa, generate_symmetric}`, their `_with_rng` variants, and
+//!   `Key::{from_ec_private_key, from_okp_private_key}`.
+//!   This pulls in the [p256](https://crates.io/crates/p256), [k256](https://crates.io/crates/k256),
+//!   [ed25519-dalek](https://crates.io/crates/ed25519-dalek),
+//!   [x25519-dalek](https://crates.io/crates/x25519-dalek), [rsa](https://crates.io/crates/rsa),
+//!   and [rand](https://crates.io/crates/rand) crates.
+//! * `jwt-convert` - enables conversions to types in the [jsonwebtoken](https://crates.io/crates/jsonwebtoken) crate.
+//! * `jwe` - enables the `jwe` module for encrypting and decrypting JSON Web Encryption tokens.
+//!   This pulls in the RustCrypto [rsa](https://crates.io/crates/rsa),
+//!   [aes-gcm](https://crates.io/crates/aes-gcm), [aes-kw](https://crates.io/crates/aes-kw),
+//!   [cbc](https://crates.io/crates/cbc), and elliptic curve crates.
+//! * `jws` - enables the `jws` module for signing and verifying JSON Web Signatures.
+//!   This pulls in the RustCrypto [rsa](https://crates.io/crates/rsa),
+//!   [hmac](https://crates.io/crates/hmac), and elliptic curve crates.
+//! * `jwt` - enables the `jwt` module for encoding, decoding, and validating JSON Web Tokens.
+//!   This implies `jws`.
+//! * `validate` - enables `Key::validate`, which checks that keys are mathematically consistent.
+//!   This pulls in the [num-bigint](https://crates.io/crates/num-bigint) and elliptic curve crates.
 
-mod byte_array;
 mod byte_vec;
+mod extensions;
+#[cfg(feature = "jwe")]
+pub mod jwe;
+mod jwk_set;
+#[cfg(feature = "jws")]
+pub mod jws;
+#[cfg(feature = "jwt")]
+pub mod jwt;
 mod key_ops;
+mod key_selection;
+mod key_usage;
+#[cfg(feature = "validate")]
+mod key_validation;
+#[cfg(feature = "pkcs-convert")]
+mod pkcs_import;
+#[cfg(feature = "pkcs-convert")]
+mod rsa_params;
 #[cfg(test)]
 mod tests;
+mod typed_jwk;
 mod utils;
 
 use std::{borrow::Cow, fmt};
 
-use generic_array::typenum::U32;
-use serde::{Deserialize, Serialize};
+#[cfg(any(feature = "generate", feature = "jwe"))]
+use p256::elliptic_curve::{self, sec1};
+#[cfg(feature = "generate")]
+use rand::{CryptoRng, RngCore};
+use serde::{Deserialize, Deserializer, Serialize};
 
-pub use byte_array::ByteArray;
 pub use byte_vec::ByteVec;
-pub use key_ops::KeyOps;
+pub use extensions::Extensions;
+pub use jwk_set::{JwkSet, PublicJwkSet};
+pub use key_ops::{KeyOp, KeyOps};
+pub use key_selection::KeyHeader;
+pub use key_usage::KeyUsageError;
+#[cfg(feature = "validate")]
+pub use key_validation::ValidationError;
+pub use typed_jwk::{PrivateJwk, PublicJwk};
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct JsonWebKey {
@@ -93,10 +133,14 @@ pub struct JsonWebKey {
     pub key_id: Option<String>,
 
     #[serde(default, rename = "alg", skip_serializing_if = "Option::is_none")]
-    pub algorithm: Option<Algorithm>,
+    pub algorithm: Option<KeyAlgorithm>,
 
     #[serde(default, flatten, skip_serializing_if = "X509Params::is_empty")]
     pub x5: X509Params,
+
+    /// Any other members.
+    #[serde(default, flatten, skip_serializing_if = "Extensions::is_empty")]
+    pub extensions: Extensions,
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
@@ -141,19 +185,78 @@ impl JsonWebKey {
             key_id: None,
             algorithm: None,
             x5: Default::default(),
+            extensions: Default::default(),
         }
     }
 
-    pub fn set_algorithm(&mut self, alg: Algorithm) -> Result<(), Error> {
-        Self::validate_algorithm(alg, &*self.key)?;
+    pub fn set_algorithm(&mut self, alg: impl Into<KeyAlgorithm>) -> Result<(), Error> {
+        let alg = alg.into();
+        Self::validate_key_algorithm(alg, &self.key)?;
         self.algorithm = Some(alg);
         Ok(())
     }
 
+    /// Parses the key like `Deserialize` does, checking only its structure. Unlike `from_str`,
+    /// this accepts an `alg`, `use`, or `key_ops` that doesn't fit the key, e.g., to inspect it.
     pub fn from_slice(bytes: impl AsRef<[u8]>) -> Result<Self, Error> {
         Ok(serde_json::from_slice(bytes.as_ref())?)
     }
 
+    /// Like `from_str`, but also rejects a `use` or `key_ops` value that isn't registered in
+    /// [RFC 7517 §4](https://tools.ietf.org/html/rfc7517#section-4), which are otherwise
+    /// kept as `KeyUse::Other` and `KeyOp::Other`.
+    pub fn from_slice_strict(bytes: impl AsRef<[u8]>) -> Result<Self, Error> {
+        let jwk = Self::from_slice(bytes)?;
+        if let Some(alg) = jwk.algorithm {
+            Self::validate_key_algorithm(alg, &jwk.key)?;
+        }
+        jwk.check_key_usage()?;
+        if let Some(KeyUse::Other(key_use)) = &jwk.key_use {
+            return Err(Error::UnregisteredKeyUse(key_use.clone()));
+        }
+        if let Some(op) = jwk.key_ops.other().next() {
+            return Err(Error::UnregisteredKeyOp(op.into()));
+        }
+        Ok(jwk)
+    }
+
+    /// Like `from_str`, but returns the problems found by `check_key_usage` alongside
+    /// the key instead of failing.
+    pub fn from_slice_lenient(
+        bytes: impl AsRef<[u8]>,
+    ) -> Result<(Self, Vec<KeyUsageError>), Error> {
+        let jwk = Self::from_slice(bytes)?;
+        if let Some(alg) = jwk.algorithm {
+            Self::validate_key_algorithm(alg, &jwk.key)?;
+        }
+        let warnings = jwk.key_usage_errors();
+        Ok((jwk, warnings))
+    }
+
+    fn validate_key_algorithm(alg: KeyAlgorithm, key: &Key) -> Result<(), Error> {
+        use KeyManagementAlgorithm::*;
+        match (alg, key) {
+            (KeyAlgorithm::Signing(alg), key) => Self::validate_algorithm(alg, key),
+            (KeyAlgorithm::KeyManagement(RsaOaep | RsaOaep256), Key::RSA { .. })
+            | (
+                KeyAlgorithm::KeyManagement(A128KW | A256KW | Direct | Pbes2Hs256A128KW),
+                Key::Symmetric { .. },
+            )
+            | (
+                KeyAlgorithm::KeyManagement(EcdhEs | EcdhEsA128KW | EcdhEsA256KW),
+                Key::EC {
+                    curve: Curve::P256 | Curve::P384 | Curve::P521,
+                    ..
+                }
+                | Key::OKP {
+                    curve: OkpCurve::X25519 | OkpCurve::X448,
+                    ..
+                },
+            ) => Ok(()),
+            _ => Err(Error::MismatchedAlgorithm),
+        }
+    }
+
     fn validate_algorithm(alg: Algorithm, key: &Key) -> Result<(), Error> {
         use Algorithm::*;
         use Key::*;
@@ -164,8 +267,44 @@ impl JsonWebKey {
                     curve: Curve::P256, ..
                 },
             )
-            | (RS256, RSA { .. })
-            | (HS256, Symmetric { .. }) => Ok(()),
+            | (
+                ES384,
+                EC {
+                    curve: Curve::P384, ..
+                },
+            )
+            | (
+                ES512,
+                EC {
+                    curve: Curve::P521, ..
+                },
+            )
+            | (
+                ES256K,
+                EC {
+                    curve: Curve::Secp256k1,
+                    ..
+                },
+            )
+            | (
+                EdDSA,
+                OKP {
+                    curve: OkpCurve::Ed25519 | OkpCurve::Ed448,
+                    ..
+                },
+            )
+            | (RS256 | RS384 | RS512 | PS256 | PS384 | PS512, RSA { .. }) => Ok(()),
+            (HS256 | HS384 | HS512, Symmetric { key }) => {
+                // https://tools.ietf.org/html/rfc7518#section-3.2
+                let min_len = alg.hmac_key_len().unwrap();
+                if key.len() < min_len {
+                    return Err(Error::InsufficientKeyLength {
+                        min_len,
+                        len: key.len(),
+                    });
+                }
+                Ok(())
+            }
             _ => Err(Error::MismatchedAlgorithm),
         }
     }
@@ -174,13 +313,11 @@ impl JsonWebKey {
 impl std::str::FromStr for JsonWebKey {
     type Err = Error;
     fn from_str(json: &str) -> Result<Self, Self::Err> {
-        let jwk = Self::from_slice(json.as_bytes())?;
-
-        let alg = match jwk.algorithm {
-            Some(alg) => alg,
-            None => return Ok(jwk),
-        };
-        Self::validate_algorithm(alg, &*jwk.key).map(|_| jwk)
+        let (jwk, warnings) = Self::from_slice_lenient(json.as_bytes())?;
+        match warnings.into_iter().next() {
+            Some(err) => Err(err.into()),
+            None => Ok(jwk),
+        }
     }
 }
 
@@ -199,15 +336,17 @@ impl std::fmt::Display for JsonWebKey {
 #[allow(clippy::upper_case_acronyms)]
 pub enum Key {
     /// An elliptic curve, as per [RFC 7518 §6.2](https://tools.ietf.org/html/rfc7518#section-6.2).
+    /// The lengths of `d`, `x`, and `y` must equal `curve.coordinate_len()`.
+    #[serde(deserialize_with = "deserialize_ec_params")]
     EC {
         #[serde(rename = "crv")]
         curve: Curve,
         #[serde(skip_serializing_if = "Option::is_none")]
-        d: Option<ByteArray<U32>>,
+        d: Option<ByteVec>,
         /// The curve point x coordinate.
-        x: ByteArray<U32>,
+        x: ByteVec,
         /// The curve point y coordinate.
-        y: ByteArray<U32>,
+        y: ByteVec,
     },
     /// An elliptic curve, as per [RFC 7518 §6.3](https://tools.ietf.org/html/rfc7518#section-6.3).
     /// See also: [RFC 3447](https://tools.ietf.org/html/rfc3447).
@@ -223,6 +362,70 @@ pub enum Key {
         #[serde(rename = "k")]
         key: ByteVec,
     },
+    /// An octet key pair, as per [RFC 8037 §2](https://tools.ietf.org/html/rfc8037#section-2).
+    /// The lengths of `d` and `x` must equal `curve.private_key_len()` and
+    /// `curve.public_key_len()`, respectively.
+    #[serde(deserialize_with = "deserialize_okp_params")]
+    OKP {
+        #[serde(rename = "crv")]
+        curve: OkpCurve,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        d: Option<ByteVec>,
+        /// The public key.
+        x: ByteVec,
+    },
+}
+
+/// Deserializes the parameters of `Key::EC`, checking that their lengths match the curve.
+fn deserialize_ec_params<'de, D: Deserializer<'de>>(
+    d: D,
+) -> Result<(Curve, Option<ByteVec>, ByteVec, ByteVec), D::Error> {
+    #[derive(Deserialize)]
+    struct EcParams {
+        crv: Curve,
+        d: Option<ByteVec>,
+        x: ByteVec,
+        y: ByteVec,
+    }
+
+    let EcParams { crv, d, x, y } = EcParams::deserialize(d)?;
+    let expected_len = crv.coordinate_len();
+    for param in d.iter().chain([&x, &y]) {
+        if param.len() != expected_len {
+            return Err(serde::de::Error::invalid_length(
+                param.len(),
+                &format!("{} base64-encoded bytes for {}", expected_len, crv).as_str(),
+            ));
+        }
+    }
+    Ok((crv, d, x, y))
+}
+
+/// Deserializes the parameters of `Key::OKP`, checking that their lengths match the curve.
+fn deserialize_okp_params<'de, D: Deserializer<'de>>(
+    d: D,
+) -> Result<(OkpCurve, Option<ByteVec>, ByteVec), D::Error> {
+    #[derive(Deserialize)]
+    struct OkpParams {
+        crv: OkpCurve,
+        d: Option<ByteVec>,
+        x: ByteVec,
+    }
+
+    let OkpParams { crv, d, x } = OkpParams::deserialize(d)?;
+    let params = d
+        .iter()
+        .map(|d| (d, crv.private_key_len()))
+        .chain([(&x, crv.public_key_len())]);
+    for (param, expected_len) in params {
+        if param.len() != expected_len {
+            return Err(serde::de::Error::invalid_length(
+                param.len(),
+                &format!("{} base64-encoded bytes for {}", expected_len, crv).as_str(),
+            ));
+        }
+    }
+    Ok((crv, d, x))
 }
 
 #[cfg(feature = "thumbprint")]
@@ -265,6 +468,13 @@ impl Key {
                 ss.serialize_field("kty", "oct")?;
                 ss.end()?;
             }
+            Self::OKP { curve, x, .. } => {
+                let mut ss = s.serialize_struct("", 3)?;
+                ss.serialize_field("crv", curve.name())?;
+                ss.serialize_field("kty", "OKP")?;
+                ss.serialize_field("x", x)?;
+                ss.end()?;
+            }
         }
         Ok(crate::utils::base64_encode(H::digest(s.into_inner())))
     }
@@ -278,6 +488,7 @@ impl Key {
             self,
             Self::Symmetric { .. }
                 | Self::EC { d: Some(_), .. }
+                | Self::OKP { d: Some(_), .. }
                 | Self::RSA {
                     private: Some(_),
                     ..
@@ -302,12 +513,25 @@ impl Key {
                 public: public.clone(),
                 private: None,
             },
+            Self::OKP { curve, x, .. } => Self::OKP {
+                curve: *curve,
+                x: x.clone(),
+                d: None,
+            },
         }))
     }
 
     /// If this key is asymmetric, encodes it as PKCS#8.
+    /// Missing RSA CRT parameters are computed, as by `RsaPrivate::complete`.
     #[cfg(feature = "pkcs-convert")]
     pub fn try_to_der(&self) -> Result<Vec<u8>, ConversionError> {
+        self.try_to_der_as(KeyEncoding::Pkcs8)
+    }
+
+    /// If this key is asymmetric, encodes it using the given `encoding`.
+    /// Fails with `ConversionError::UnsupportedEncoding` if the encoding can't represent this key.
+    #[cfg(feature = "pkcs-convert")]
+    pub fn try_to_der_as(&self, encoding: KeyEncoding) -> Result<Vec<u8>, ConversionError> {
         use num_bigint::BigUint;
         use yasna::{models::ObjectIdentifier, DERWriter, DERWriterSeq, Tag};
 
@@ -318,10 +542,10 @@ impl Key {
         }
 
         Ok(match self {
-            Self::EC { d, x, y, .. } => {
+            Self::EC { curve, d, x, y } => {
                 let ec_public_oid = ObjectIdentifier::from_slice(&[1, 2, 840, 10045, 2, 1]);
-                let prime256v1_oid = ObjectIdentifier::from_slice(&[1, 2, 840, 10045, 3, 1, 7]);
-                let oids = &[Some(&ec_public_oid), Some(&prime256v1_oid)];
+                let curve_oid = curve.oid();
+                let oids = &[Some(&ec_public_oid), Some(&curve_oid)];
 
                 let write_public = |writer: DERWriter<'_>| {
                     let public_bytes: Vec<u8> = [0x04 /* uncompressed */]
@@ -330,24 +554,35 @@ impl Key {
                         .chain(y.iter())
                         .copied()
                         .collect();
-                    writer.write_bitvec_bytes(&public_bytes, 8 * (32 * 2 + 1));
+                    writer.write_bitvec_bytes(&public_bytes, 8 * public_bytes.len());
                 };
 
-                match d {
-                    Some(private_point) => {
-                        pkcs8::write_private(oids, |writer: &mut DERWriterSeq<'_>| {
-                            writer.next().write_i8(1); // version
-                            writer.next().write_bytes(&**private_point);
-                            // The following tagged value is optional. OpenSSL produces it,
-                            // but many tools, including jwt.io and `jsonwebtoken`, don't like it,
-                            // so we don't include it.
-                            // writer.next().write_tagged(Tag::context(0), |writer| {
-                            //     writer.write_oid(&prime256v1_oid)
-                            // });
-                            writer.next().write_tagged(Tag::context(1), write_public);
-                        })
+                // https://tools.ietf.org/html/rfc5915#section-3
+                let write_private = |writer: DERWriter<'_>, private_point: &ByteVec| {
+                    writer.write_sequence(|writer| {
+                        writer.next().write_i8(1); // version
+                        writer.next().write_bytes(private_point);
+                        // The curve parameter is optional within PKCS#8, which already names the
+                        // curve. OpenSSL produces it, but many tools, including jwt.io and
+                        // `jsonwebtoken`, don't like it, so it's only included in bare SEC1.
+                        if encoding == KeyEncoding::Sec1 {
+                            writer.next().write_tagged(Tag::context(0), |writer| {
+                                writer.write_oid(&curve_oid)
+                            });
+                        }
+                        writer.next().write_tagged(Tag::context(1), write_public);
+                    })
+                };
+
+                match (d, encoding) {
+                    (Some(private_point), KeyEncoding::Pkcs8) => {
+                        pkcs8::write_private(oids, |writer| write_private(writer, private_point))
                     }
-                    None => pkcs8::write_public(oids, write_public),
+                    (None, KeyEncoding::Pkcs8) => pkcs8::write_public(oids, write_public),
+                    (Some(private_point), KeyEncoding::Sec1) => {
+                        yasna::construct_der(|writer| write_private(writer, private_point))
+                    }
+                    _ => return Err(ConversionError::UnsupportedEncoding(encoding)),
                 }
             }
             Self::RSA { public, private } => {
@@ -362,7 +597,7 @@ impl Key {
 
                 let write_public = |writer: &mut DERWriterSeq<'_>| {
                     write_bytevec(writer.next(), &public.n);
-                    writer.next().write_u32(PUBLIC_EXPONENT);
+                    write_bytevec(writer.next(), &public.e);
                 };
 
                 let write_private = |writer: &mut DERWriterSeq<'_>, private: &RsaPrivate| {
@@ -378,7 +613,8 @@ impl Key {
                     write_opt_bytevecs!(p, q, dp, dq, qi);
                 };
 
-                match private {
+                let completed;
+                let private = match private {
                     Some(
                         private @ RsaPrivate {
                             d: _,
@@ -388,13 +624,51 @@ impl Key {
                             dq: Some(_),
                             qi: Some(_),
                         },
-                    ) => pkcs8::write_private(oids, |writer| write_private(writer, private)),
-                    Some(_) => return Err(ConversionError::MissingRsaParams),
-                    None => pkcs8::write_public(oids, |writer| {
+                    ) => Some(private),
+                    Some(private) => {
+                        let mut private = private.clone();
+                        private.complete(public)?;
+                        completed = private;
+                        Some(&completed)
+                    }
+                    None => None,
+                };
+
+                match (private, encoding) {
+                    (Some(private), KeyEncoding::Pkcs8) => pkcs8::write_private(oids, |writer| {
+                        writer.write_sequence(|writer| write_private(writer, private))
+                    }),
+                    (None, KeyEncoding::Pkcs8) => pkcs8::write_public(oids, |writer| {
                         let body =
                             yasna::construct_der(|writer| writer.write_sequence(write_public));
                         writer.write_bitvec_bytes(&body, body.len() * 8);
                     }),
+                    (Some(private), KeyEncoding::Pkcs1) => yasna::construct_der(|writer| {
+                        writer.write_sequence(|writer| write_private(writer, private))
+                    }),
+                    (None, KeyEncoding::Pkcs1) => {
+                        // https://tools.ietf.org/html/rfc3447#appendix-A.1.1
+                        yasna::construct_der(|writer| writer.write_sequence(write_public))
+                    }
+                    (_, KeyEncoding::Sec1) => {
+                        return Err(ConversionError::UnsupportedEncoding(encoding))
+                    }
+                }
+            }
+            Self::OKP { curve, d, x } => {
+                if encoding != KeyEncoding::Pkcs8 {
+                    return Err(ConversionError::UnsupportedEncoding(encoding));
+                }
+                // https://tools.ietf.org/html/rfc8410#section-7
+                let curve_oid = curve.oid();
+                let oids = &[Some(&curve_oid)];
+                match d {
+                    Some(private_key) => pkcs8::write_private(oids, |writer| {
+                        writer.write_bytes(private_key);
+                    }),
+                    None => pkcs8::write_public(oids, |writer| {
+                        writer.write_bitvec_bytes(x, x.len() * 8);
+                    }),
                 }
             }
             Self::Symmetric { .. } => unreachable!("checked above"),
@@ -402,7 +676,7 @@ impl Key {
     }
 
     /// Unwrapping `try_to_der`.
-    /// Panics if the key is not asymmetric or there are missing RSA components.
+    /// Panics if the key is not asymmetric or the RSA parameters are inconsistent.
     #[cfg(feature = "pkcs-convert")]
     pub fn to_der(&self) -> Vec<u8> {
         self.try_to_der().unwrap()
@@ -411,15 +685,23 @@ impl Key {
     /// If this key is asymmetric, encodes it as PKCS#8 with PEM armoring.
     #[cfg(feature = "pkcs-convert")]
     pub fn try_to_pem(&self) -> Result<String, ConversionError> {
+        self.try_to_pem_as(KeyEncoding::Pkcs8)
+    }
+
+    /// If this key is asymmetric, encodes it using the given `encoding` with PEM armoring.
+    #[cfg(feature = "pkcs-convert")]
+    pub fn try_to_pem_as(&self, encoding: KeyEncoding) -> Result<String, ConversionError> {
         use std::fmt::Write;
-        let der_b64 = base64::encode(self.try_to_der()?);
-        let key_ty = if self.is_private() {
-            "PRIVATE"
-        } else {
-            "PUBLIC"
+        let der_b64 = base64::encode(self.try_to_der_as(encoding)?);
+        let label = match (encoding, self.is_private()) {
+            (KeyEncoding::Pkcs8, true) => "PRIVATE KEY",
+            (KeyEncoding::Pkcs8, false) => "PUBLIC KEY",
+            (KeyEncoding::Pkcs1, true) => "RSA PRIVATE KEY",
+            (KeyEncoding::Pkcs1, false) => "RSA PUBLIC KEY",
+            (KeyEncoding::Sec1, _) => "EC PRIVATE KEY",
         };
         let mut pem = String::new();
-        writeln!(&mut pem, "-----BEGIN {} KEY-----", key_ty).unwrap();
+        writeln!(&mut pem, "-----BEGIN {}-----", label).unwrap();
         //^ re: `unwrap`, if writing to a string fails, we've got bigger issues.
         const MAX_LINE_LEN: usize = 64;
         for i in (0..der_b64.len()).step_by(MAX_LINE_LEN) {
@@ -430,7 +712,7 @@ impl Key {
             )
             .unwrap();
         }
-        writeln!(&mut pem, "-----END {} KEY-----", key_ty).unwrap();
+        writeln!(&mut pem, "-----END {}-----", label).unwrap();
         Ok(pem)
     }
 
@@ -445,9 +727,17 @@ impl Key {
     /// Best used with one of the HS algorithms (e.g., HS256).
     #[cfg(feature = "generate")]
     pub fn generate_symmetric(num_bits: usize) -> Self {
-        use rand::RngCore;
+        Self::generate_symmetric_with_rng(num_bits, &mut rand::thread_rng())
+    }
+
+    /// Like `generate_symmetric`, but draws the key from `rng`.
+    #[cfg(feature = "generate")]
+    pub fn generate_symmetric_with_rng<R: CryptoRng + RngCore>(
+        num_bits: usize,
+        rng: &mut R,
+    ) -> Self {
         let mut bytes = vec![0; num_bits / 8];
-        rand::thread_rng().fill_bytes(&mut bytes);
+        rng.fill_bytes(&mut bytes);
         Self::Symmetric { key: bytes.into() }
     }
 
@@ -455,23 +745,172 @@ impl Key {
     /// Used with the ES256 algorithm.
     #[cfg(feature = "generate")]
     pub fn generate_p256() -> Self {
-        use p256::elliptic_curve::{self as elliptic_curve, sec1::ToEncodedPoint};
+        Self::generate_p256_with_rng(&mut rand::thread_rng())
+    }
+
+    /// Like `generate_p256`, but draws the key from `rng`.
+    #[cfg(feature = "generate")]
+    pub fn generate_p256_with_rng<R: CryptoRng + RngCore>(rng: &mut R) -> Self {
+        let sk = p256::SecretKey::random(rng);
+        Self::from_ec_secret_key(Curve::P256, &sk)
+    }
+
+    /// Generates a new EC keypair using the secp256k1 curve.
+    /// Used with the ES256K algorithm.
+    #[cfg(feature = "generate")]
+    pub fn generate_secp256k1() -> Self {
+        Self::generate_secp256k1_with_rng(&mut rand::thread_rng())
+    }
 
-        let sk = elliptic_curve::SecretKey::random(&mut rand::thread_rng());
-        let sk_scalar = p256::Scalar::from(&sk);
+    /// Like `generate_secp256k1`, but draws the key from `rng`.
+    #[cfg(feature = "generate")]
+    pub fn generate_secp256k1_with_rng<R: CryptoRng + RngCore>(rng: &mut R) -> Self {
+        let sk = k256::SecretKey::random(rng);
+        Self::from_ec_secret_key(Curve::Secp256k1, &sk)
+    }
 
-        let pk = p256::ProjectivePoint::GENERATOR * sk_scalar;
-        let pk_bytes = &pk
-            .to_affine()
-            .to_encoded_point(false /* compress */)
-            .to_bytes()[1..];
-        let (x_bytes, y_bytes) = pk_bytes.split_at(32);
+    /// Generates a new Ed25519 keypair.
+    /// Used with the EdDSA algorithm.
+    #[cfg(feature = "generate")]
+    pub fn generate_ed25519() -> Self {
+        Self::generate_ed25519_with_rng(&mut rand::thread_rng())
+    }
+
+    /// Like `generate_ed25519`, but draws the key from `rng`.
+    #[cfg(feature = "generate")]
+    pub fn generate_ed25519_with_rng<R: CryptoRng + RngCore>(rng: &mut R) -> Self {
+        let sk = ed25519_dalek::SigningKey::generate(rng);
+        Self::OKP {
+            curve: OkpCurve::Ed25519,
+            d: Some(sk.to_bytes().to_vec().into()),
+            x: sk.verifying_key().to_bytes().to_vec().into(),
+        }
+    }
+
+    /// Generates a new X25519 keypair.
+    /// Used for ECDH-ES key agreement.
+    #[cfg(feature = "generate")]
+    pub fn generate_x25519() -> Self {
+        Self::generate_x25519_with_rng(&mut rand::thread_rng())
+    }
+
+    /// Like `generate_x25519`, but draws the key from `rng`.
+    #[cfg(feature = "generate")]
+    pub fn generate_x25519_with_rng<R: CryptoRng + RngCore>(rng: &mut R) -> Self {
+        let sk = x25519_dalek::StaticSecret::random_from_rng(rng);
+        Self::OKP {
+            curve: OkpCurve::X25519,
+            d: Some(sk.to_bytes().to_vec().into()),
+            x: x25519_dalek::PublicKey::from(&sk)
+                .to_bytes()
+                .to_vec()
+                .into(),
+        }
+    }
+
+    /// Generates a new RSA keypair, with all CRT parameters, whose modulus has `num_bits` bits.
+    /// Used with the RS, PS, and RSA-OAEP algorithms. Fails if `num_bits` is less than 2048.
+    #[cfg(feature = "generate")]
+    pub fn generate_rsa(num_bits: usize) -> Result<Self, Error> {
+        Self::generate_rsa_with_rng(num_bits, &mut rand::thread_rng())
+    }
+
+    /// Like `generate_rsa`, but draws the primes from `rng`.
+    #[cfg(feature = "generate")]
+    pub fn generate_rsa_with_rng<R: CryptoRng + RngCore>(
+        num_bits: usize,
+        rng: &mut R,
+    ) -> Result<Self, Error> {
+        use rsa::traits::{PrivateKeyParts, PublicKeyParts};
+
+        if num_bits < MIN_RSA_KEY_BITS {
+            return Err(Error::UnsupportedKeySize(num_bits));
+        }
+        let sk = rsa::RsaPrivateKey::new(rng, num_bits).map_err(Error::KeyGeneration)?;
+        let uint = |n: &rsa::BigUint| ByteVec::from(n.to_bytes_be());
+        let (p, q) = match sk.primes() {
+            [p, q] => (p, q),
+            _ => unreachable!("the key has two primes"),
+        };
+        Ok(Self::RSA {
+            public: RsaPublic {
+                e: uint(sk.e()),
+                n: uint(sk.n()),
+            },
+            private: Some(RsaPrivate {
+                d: uint(sk.d()),
+                p: Some(uint(p)),
+                q: Some(uint(q)),
+                dp: sk.dp().map(uint),
+                dq: sk.dq().map(uint),
+                qi: sk.crt_coefficient().as_ref().map(uint),
+            }),
+        })
+    }
+
+    /// Creates an EC keypair from the big-endian private scalar, deriving the public point.
+    /// Use `Key::validate` to instead check that an existing `d` matches its `x` and `y`.
+    #[cfg(feature = "generate")]
+    pub fn from_ec_private_key(curve: Curve, d: impl AsRef<[u8]>) -> Result<Self, Error> {
+        macro_rules! from_private_key {
+            ($krate:ident) => {
+                $krate::SecretKey::from_slice(d.as_ref())
+                    .map(|sk| Self::from_ec_secret_key(curve, &sk))
+                    .map_err(|_| Error::InvalidPrivateKey)
+            };
+        }
+        match curve {
+            Curve::P256 => from_private_key!(p256),
+            Curve::P384 => from_private_key!(p384),
+            Curve::P521 => from_private_key!(p521),
+            Curve::Secp256k1 => from_private_key!(k256),
+        }
+    }
+
+    /// Creates an OKP keypair from the private key, deriving the public key.
+    /// Only Ed25519 and X25519 are supported.
+    #[cfg(feature = "generate")]
+    pub fn from_okp_private_key(curve: OkpCurve, d: impl AsRef<[u8]>) -> Result<Self, Error> {
+        let d: [u8; 32] = match curve {
+            OkpCurve::Ed25519 | OkpCurve::X25519 => d
+                .as_ref()
+                .try_into()
+                .map_err(|_| Error::InvalidPrivateKey)?,
+            OkpCurve::Ed448 | OkpCurve::X448 => return Err(Error::UnsupportedCurve(curve.name())),
+        };
+        let x = match curve {
+            OkpCurve::Ed25519 => ed25519_dalek::SigningKey::from_bytes(&d)
+                .verifying_key()
+                .to_bytes(),
+            _ => x25519_dalek::PublicKey::from(&x25519_dalek::StaticSecret::from(d)).to_bytes(),
+        };
+        Ok(Self::OKP {
+            curve,
+            d: Some(d.to_vec().into()),
+            x: x.to_vec().into(),
+        })
+    }
+
+    #[cfg(any(feature = "generate", feature = "jwe"))]
+    pub(crate) fn from_ec_secret_key<C>(curve: Curve, sk: &elliptic_curve::SecretKey<C>) -> Self
+    where
+        C: elliptic_curve::CurveArithmetic,
+        elliptic_curve::AffinePoint<C>: sec1::FromEncodedPoint<C> + sec1::ToEncodedPoint<C>,
+        elliptic_curve::FieldBytesSize<C>: sec1::ModulusSize,
+    {
+        use sec1::{Coordinates, ToEncodedPoint};
+
+        let pk = sk.public_key().to_encoded_point(false /* compress */);
+        let (x, y) = match pk.coordinates() {
+            Coordinates::Uncompressed { x, y } => (x, y),
+            _ => unreachable!("the point is uncompressed and not the identity"),
+        };
 
         Self::EC {
-            curve: Curve::P256,
-            d: Some(sk_scalar.to_bytes().into()),
-            x: ByteArray::from_slice(x_bytes),
-            y: ByteArray::from_slice(y_bytes),
+            curve,
+            d: Some(sk.to_bytes().to_vec().into()),
+            x: x.to_vec().into(),
+            y: y.to_vec().into(),
         }
     }
 }
@@ -481,14 +920,53 @@ pub enum Curve {
     /// The prime256v1 (P256) curve.
     #[serde(rename = "P-256")]
     P256,
+    /// The secp384r1 (P384) curve.
+    #[serde(rename = "P-384")]
+    P384,
+    /// The secp521r1 (P521) curve.
+    #[serde(rename = "P-521")]
+    P521,
+    /// The secp256k1 curve, as per [RFC 8812](https://tools.ietf.org/html/rfc8812#section-3.1).
+    #[serde(rename = "secp256k1")]
+    Secp256k1,
 }
 
 impl Curve {
     pub fn name(&self) -> &'static str {
         match self {
             Self::P256 => "P-256",
+            Self::P384 => "P-384",
+            Self::P521 => "P-521",
+            Self::Secp256k1 => "secp256k1",
+        }
+    }
+
+    /// The number of bytes in an encoded coordinate or private key of this curve.
+    pub fn coordinate_len(&self) -> usize {
+        match self {
+            Self::P256 | Self::Secp256k1 => 32,
+            Self::P384 => 48,
+            Self::P521 => 66,
         }
     }
+
+    #[cfg(feature = "pkcs-convert")]
+    fn oid(&self) -> yasna::models::ObjectIdentifier {
+        use yasna::models::ObjectIdentifier;
+        match self {
+            Self::P256 => ObjectIdentifier::from_slice(&[1, 2, 840, 10045, 3, 1, 7]),
+            Self::P384 => ObjectIdentifier::from_slice(&[1, 3, 132, 0, 34]),
+            Self::P521 => ObjectIdentifier::from_slice(&[1, 3, 132, 0, 35]),
+            Self::Secp256k1 => ObjectIdentifier::from_slice(&[1, 3, 132, 0, 10]),
+        }
+    }
+
+    #[cfg(feature = "pkcs-convert")]
+    fn from_oid(oid: &yasna::models::ObjectIdentifier) -> Option<Self> {
+        [Self::P256, Self::P384, Self::P521, Self::Secp256k1]
+            .into_iter()
+            .find(|curve| curve.oid() == *oid)
+    }
 }
 
 impl fmt::Display for Curve {
@@ -497,39 +975,110 @@ impl fmt::Display for Curve {
     }
 }
 
+/// The curves of octet key pairs, as per [RFC 8037 §3](https://tools.ietf.org/html/rfc8037#section-3).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OkpCurve {
+    /// The Ed25519 signature curve.
+    Ed25519,
+    /// The Ed448 signature curve.
+    Ed448,
+    /// The X25519 key agreement function.
+    X25519,
+    /// The X448 key agreement function.
+    X448,
+}
+
+impl OkpCurve {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Ed25519 => "Ed25519",
+            Self::Ed448 => "Ed448",
+            Self::X25519 => "X25519",
+            Self::X448 => "X448",
+        }
+    }
+
+    /// The number of bytes in an encoded public key of this curve.
+    pub fn public_key_len(&self) -> usize {
+        match self {
+            Self::Ed25519 | Self::X25519 => 32,
+            Self::Ed448 => 57,
+            Self::X448 => 56,
+        }
+    }
+
+    /// The number of bytes in an encoded private key of this curve.
+    pub fn private_key_len(&self) -> usize {
+        self.public_key_len()
+    }
+
+    #[cfg(feature = "pkcs-convert")]
+    fn oid(&self) -> yasna::models::ObjectIdentifier {
+        use yasna::models::ObjectIdentifier;
+        match self {
+            Self::X25519 => ObjectIdentifier::from_slice(&[1, 3, 101, 110]),
+            Self::X448 => ObjectIdentifier::from_slice(&[1, 3, 101, 111]),
+            Self::Ed25519 => ObjectIdentifier::from_slice(&[1, 3, 101, 112]),
+            Self::Ed448 => ObjectIdentifier::from_slice(&[1, 3, 101, 113]),
+        }
+    }
+
+    #[cfg(feature = "pkcs-convert")]
+    fn from_oid(oid: &yasna::models::ObjectIdentifier) -> Option<Self> {
+        [Self::Ed25519, Self::Ed448, Self::X25519, Self::X448]
+            .into_iter()
+            .find(|curve| curve.oid() == *oid)
+    }
+}
+
+impl fmt::Display for OkpCurve {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RsaPublic {
-    /// The standard public exponent, 65537.
-    pub e: PublicExponent,
+    /// The public exponent, big-endian. Usually 65537 (`AQAB`).
+    #[serde(deserialize_with = "deserialize_public_exponent")]
+    pub e: ByteVec,
     /// The modulus, p*q.
     pub n: ByteVec,
 }
 
-const PUBLIC_EXPONENT: u32 = 65537;
-const PUBLIC_EXPONENT_B64: &str = "AQAB"; // little-endian, strip zeros
-const PUBLIC_EXPONENT_B64_PADDED: &str = "AQABAA==";
+/// The smallest RSA modulus that `Key::generate_rsa` will generate, in bits.
+#[cfg(feature = "generate")]
+const MIN_RSA_KEY_BITS: usize = 2048;
 
 /// The standard RSA public exponent, 65537.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub struct PublicExponent;
+const PUBLIC_EXPONENT: u32 = 65537;
+
+/// The little-endian encoding of 65537 that earlier versions of this crate accepted.
+/// Read as big-endian, it would be 65792.
+const PUBLIC_EXPONENT_B64_PADDED: &str = "AQABAA==";
 
-impl Serialize for PublicExponent {
-    fn serialize<S: serde::ser::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
-        PUBLIC_EXPONENT_B64.serialize(s)
+fn deserialize_public_exponent<'de, D: Deserializer<'de>>(d: D) -> Result<ByteVec, D::Error> {
+    use serde::de::IntoDeserializer;
+    let e = String::deserialize(d)?;
+    if e == PUBLIC_EXPONENT_B64_PADDED {
+        return Ok(PUBLIC_EXPONENT.to_be_bytes()[1..].to_vec().into());
     }
+    crate::utils::serde_base64::deserialize(e.into_deserializer()).map(ByteVec::from)
 }
 
-impl<'de> Deserialize<'de> for PublicExponent {
-    fn deserialize<D: serde::de::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
-        let e = String::deserialize(d)?;
-        if e == PUBLIC_EXPONENT_B64 || e == PUBLIC_EXPONENT_B64_PADDED {
-            Ok(Self)
-        } else {
-            Err(serde::de::Error::custom(&format!(
-                "public exponent must be {}",
-                PUBLIC_EXPONENT
-            )))
-        }
+impl RsaPublic {
+    /// Returns true iff the public exponent is even or smaller than the standard exponent, 65537.
+    /// Such exponents are legal, but should be rejected by callers that do not need to support
+    /// legacy keys, as per [NIST SP 800-56B §6.2.1](https://doi.org/10.6028/NIST.SP.800-56Br2).
+    pub fn has_weak_exponent(&self) -> bool {
+        let e = match self.e.iter().position(|&b| b != 0) {
+            Some(start) => &self.e[start..],
+            None => return true,
+        };
+        let is_even = e[e.len() - 1] & 1 == 0;
+        let is_small = e.len() <= 4
+            && e.iter().fold(0u32, |acc, &b| (acc << 8) | u32::from(b)) < PUBLIC_EXPONENT;
+        is_even || is_small
     }
 }
 
@@ -560,42 +1109,247 @@ impl fmt::Debug for RsaPrivate {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// The ASN.1 structure used when encoding an asymmetric key as DER or PEM.
+#[cfg(feature = "pkcs-convert")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum KeyEncoding {
+    /// PKCS#8 for private keys and SPKI for public keys. Supports all asymmetric keys.
+    Pkcs8,
+    /// PKCS#1 (`RSA PRIVATE KEY`/`RSA PUBLIC KEY`). Supports only RSA keys.
+    Pkcs1,
+    /// SEC1 (`EC PRIVATE KEY`). Supports only private EC keys.
+    Sec1,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
 pub enum KeyUse {
-    #[serde(rename = "sig")]
+    /// sig
     Signing,
-    #[serde(rename = "enc")]
+    /// enc
     Encryption,
+    /// A use that isn't registered.
+    Other(String),
+}
+
+impl KeyUse {
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Signing => "sig",
+            Self::Encryption => "enc",
+            Self::Other(name) => name,
+        }
+    }
+}
+
+impl From<String> for KeyUse {
+    fn from(name: String) -> Self {
+        match name.as_str() {
+            "sig" => Self::Signing,
+            "enc" => Self::Encryption,
+            _ => Self::Other(name),
+        }
+    }
+}
+
+impl From<KeyUse> for String {
+    fn from(key_use: KeyUse) -> Self {
+        match key_use {
+            KeyUse::Other(name) => name,
+            key_use => key_use.name().into(),
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[allow(clippy::upper_case_acronyms)]
 pub enum Algorithm {
     HS256,
+    HS384,
+    HS512,
     RS256,
+    RS384,
+    RS512,
+    PS256,
+    PS384,
+    PS512,
     ES256,
+    ES384,
+    ES512,
+    /// ECDSA using secp256k1 and SHA-256, as per [RFC 8812](https://tools.ietf.org/html/rfc8812#section-3.2).
+    ES256K,
+    /// Edwards-curve signatures, as per [RFC 8037 §3.1](https://tools.ietf.org/html/rfc8037#section-3.1).
+    EdDSA,
 }
 
 impl Algorithm {
     pub fn name(&self) -> &'static str {
         match self {
             Self::HS256 => "hs256",
+            Self::HS384 => "hs384",
+            Self::HS512 => "hs512",
             Self::RS256 => "rs256",
+            Self::RS384 => "rs384",
+            Self::RS512 => "rs512",
+            Self::PS256 => "ps256",
+            Self::PS384 => "ps384",
+            Self::PS512 => "ps512",
             Self::ES256 => "es256",
+            Self::ES384 => "es384",
+            Self::ES512 => "es512",
+            Self::ES256K => "es256k",
+            Self::EdDSA => "eddsa",
         }
     }
+
+    /// The minimum number of bytes in a key used with this algorithm, if it is an HMAC
+    /// algorithm. This is the size of the hash output.
+    pub fn hmac_key_len(&self) -> Option<usize> {
+        match self {
+            Self::HS256 => Some(32),
+            Self::HS384 => Some(48),
+            Self::HS512 => Some(64),
+            _ => None,
+        }
+    }
+}
+
+/// The algorithm used to determine the content encryption key, as per
+/// [RFC 7518 §4.1](https://tools.ietf.org/html/rfc7518#section-4.1).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[allow(clippy::upper_case_acronyms)]
+pub enum KeyManagementAlgorithm {
+    /// RSAES OAEP using SHA-1 and MGF1 with SHA-1.
+    #[serde(rename = "RSA-OAEP")]
+    RsaOaep,
+    /// RSAES OAEP using SHA-256 and MGF1 with SHA-256.
+    #[serde(rename = "RSA-OAEP-256")]
+    RsaOaep256,
+    A128KW,
+    A256KW,
+    /// The symmetric key is used directly as the content encryption key.
+    #[serde(rename = "dir")]
+    Direct,
+    /// The content encryption key is agreed upon using ECDH with an ephemeral key.
+    #[serde(rename = "ECDH-ES")]
+    EcdhEs,
+    #[serde(rename = "ECDH-ES+A128KW")]
+    EcdhEsA128KW,
+    #[serde(rename = "ECDH-ES+A256KW")]
+    EcdhEsA256KW,
+    /// The content encryption key is wrapped using a key derived from a password.
+    /// See `jwe::encrypt_with_password`.
+    #[serde(rename = "PBES2-HS256+A128KW")]
+    Pbes2Hs256A128KW,
+}
+
+impl KeyManagementAlgorithm {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::RsaOaep => "RSA-OAEP",
+            Self::RsaOaep256 => "RSA-OAEP-256",
+            Self::A128KW => "A128KW",
+            Self::A256KW => "A256KW",
+            Self::Direct => "dir",
+            Self::EcdhEs => "ECDH-ES",
+            Self::EcdhEsA128KW => "ECDH-ES+A128KW",
+            Self::EcdhEsA256KW => "ECDH-ES+A256KW",
+            Self::Pbes2Hs256A128KW => "PBES2-HS256+A128KW",
+        }
+    }
+
+    /// The `key_ops` that a key must permit to encrypt using this algorithm:
+    /// `encrypt` for `dir`, and `wrapKey` otherwise.
+    pub fn encryption_ops(&self) -> KeyOps {
+        match self {
+            Self::Direct => KeyOps::ENCRYPT,
+            _ => KeyOps::WRAP_KEY,
+        }
+    }
+
+    /// The `key_ops` that a key must permit to decrypt using this algorithm:
+    /// `decrypt` for `dir`, and `unwrapKey` otherwise.
+    pub fn decryption_ops(&self) -> KeyOps {
+        match self {
+            Self::Direct => KeyOps::DECRYPT,
+            _ => KeyOps::UNWRAP_KEY,
+        }
+    }
+
+    /// The number of bytes in the AES key wrapping key, if this algorithm uses AES key wrap.
+    #[cfg(feature = "jwe")]
+    pub(crate) fn wrap_key_len(&self) -> Option<usize> {
+        match self {
+            Self::A128KW | Self::EcdhEsA128KW | Self::Pbes2Hs256A128KW => Some(16),
+            Self::A256KW | Self::EcdhEsA256KW => Some(32),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for KeyManagementAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The `alg` of a JWK, which identifies the algorithm that the key is intended for, as per
+/// [RFC 7517 §4.4](https://tools.ietf.org/html/rfc7517#section-4.4).
+/// Keys for signing name a JWS algorithm, and keys for encryption a JWE key management algorithm.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged, expecting = "a JWS or JWE key management algorithm")]
+pub enum KeyAlgorithm {
+    Signing(Algorithm),
+    KeyManagement(KeyManagementAlgorithm),
+}
+
+impl From<Algorithm> for KeyAlgorithm {
+    fn from(alg: Algorithm) -> Self {
+        Self::Signing(alg)
+    }
+}
+
+impl From<KeyManagementAlgorithm> for KeyAlgorithm {
+    fn from(alg: KeyManagementAlgorithm) -> Self {
+        Self::KeyManagement(alg)
+    }
 }
 
 #[cfg(feature = "jwt-convert")]
-const _IMPL_JWT_CONVERSIONS: () = {
+const _: () = {
     use jsonwebtoken as jwt;
 
-    impl From<Algorithm> for jwt::Algorithm {
-        fn from(alg: Algorithm) -> Self {
-            match alg {
+    impl TryFrom<Algorithm> for jwt::Algorithm {
+        type Error = ConversionError;
+
+        fn try_from(alg: Algorithm) -> Result<Self, Self::Error> {
+            Ok(match alg {
                 Algorithm::HS256 => Self::HS256,
+                Algorithm::HS384 => Self::HS384,
+                Algorithm::HS512 => Self::HS512,
                 Algorithm::ES256 => Self::ES256,
+                Algorithm::ES384 => Self::ES384,
                 Algorithm::RS256 => Self::RS256,
+                Algorithm::RS384 => Self::RS384,
+                Algorithm::RS512 => Self::RS512,
+                Algorithm::PS256 => Self::PS256,
+                Algorithm::PS384 => Self::PS384,
+                Algorithm::PS512 => Self::PS512,
+                Algorithm::EdDSA => Self::EdDSA,
+                Algorithm::ES512 | Algorithm::ES256K => {
+                    return Err(ConversionError::UnsupportedAlgorithm(alg))
+                }
+            })
+        }
+    }
+
+    impl TryFrom<KeyAlgorithm> for jwt::Algorithm {
+        type Error = ConversionError;
+
+        fn try_from(alg: KeyAlgorithm) -> Result<Self, Self::Error> {
+            match alg {
+                KeyAlgorithm::Signing(alg) => alg.try_into(),
+                KeyAlgorithm::KeyManagement(alg) => Err(ConversionError::NotSigningAlgorithm(alg)),
             }
         }
     }
@@ -616,6 +1370,7 @@ const _IMPL_JWT_CONVERSIONS: () = {
                 Self::RSA { .. } => {
                     jwt::EncodingKey::from_rsa_pem(self.try_to_pem()?.as_bytes()).unwrap()
                 }
+                Self::OKP { .. } => jwt::EncodingKey::from_ed_der(&self.try_to_der()?),
             })
         }
 
@@ -637,6 +1392,8 @@ const _IMPL_JWT_CONVERSIONS: () = {
                 Self::RSA { .. } => {
                     jwt::DecodingKey::from_rsa_pem(self.to_pem().as_bytes()).unwrap()
                 }
+                // `jsonwebtoken` expects the raw public key, which is exactly `x`.
+                Self::OKP { x, .. } => jwt::DecodingKey::from_ed_der(x),
             }
         }
     }
@@ -652,17 +1409,91 @@ pub enum Error {
 
     #[error("mismatched algorithm for key type")]
     MismatchedAlgorithm,
+
+    #[error("the algorithm requires a key of at least {min_len} bytes, but the key has {len}")]
+    InsufficientKeyLength { min_len: usize, len: usize },
+
+    #[error(transparent)]
+    KeyUsage(#[from] KeyUsageError),
+
+    #[error("unregistered key use: `{0}`")]
+    UnregisteredKeyUse(String),
+
+    #[error("unregistered key operation: `{0}`")]
+    UnregisteredKeyOp(String),
+
+    #[error("`{0}` is a registered JWK member, not an extension")]
+    RegisteredMember(String),
+
+    #[error("unsupported extension: `{0}`")]
+    UnsupportedExtension(String),
+
+    #[error("unsupported key size: {0} bits")]
+    UnsupportedKeySize(usize),
+
+    #[cfg(feature = "generate")]
+    #[error("key generation failed")]
+    KeyGeneration(#[source] rsa::Error),
+
+    #[error("invalid private key")]
+    InvalidPrivateKey,
+
+    #[error("unsupported curve: {0}")]
+    UnsupportedCurve(&'static str),
+
+    #[error("expected an asymmetric private key")]
+    ExpectedPrivateKey,
+
+    #[error("expected a public key")]
+    ExpectedPublicKey,
 }
 
 #[derive(Debug, thiserror::Error)]
 pub enum ConversionError {
-    #[error("encoding RSA JWK as PKCS#8 requires specifing all of p, q, dp, dq, qi")]
-    MissingRsaParams,
+    #[cfg(feature = "pkcs-convert")]
+    #[error("the RSA parameters are inconsistent")]
+    InvalidRsaParams,
 
     #[error("a symmetric key can not be encoded using PKCS#8")]
     NotAsymmetric,
 
+    #[cfg(feature = "pkcs-convert")]
+    #[error("invalid DER: {0}")]
+    Der(#[from] yasna::ASN1Error),
+
+    #[cfg(feature = "pkcs-convert")]
+    #[error("invalid PEM")]
+    Pem,
+
+    #[cfg(feature = "pkcs-convert")]
+    #[error("unsupported PEM label: `{0}`")]
+    UnsupportedPemLabel(String),
+
+    #[cfg(feature = "pkcs-convert")]
+    #[error("unsupported key algorithm: {0}")]
+    UnsupportedKeyAlgorithm(yasna::models::ObjectIdentifier),
+
+    #[cfg(feature = "pkcs-convert")]
+    #[error("unsupported curve: {0}")]
+    UnsupportedCurve(yasna::models::ObjectIdentifier),
+
+    #[cfg(feature = "pkcs-convert")]
+    #[error("the encoded private key does not contain its public key")]
+    MissingPublicKey,
+
+    #[cfg(feature = "pkcs-convert")]
+    #[error("the key can not be encoded using {0:?}")]
+    UnsupportedEncoding(KeyEncoding),
+
     #[cfg(feature = "jwt-convert")]
     #[error("a public key cannot be converted to a `jsonwebtoken::EncodingKey`")]
     NotPrivate,
+
+    #[cfg(feature = "jwt-convert")]
+    #[error("the {0:?} algorithm is not supported by `jsonwebtoken`")]
+    UnsupportedAlgorithm(Algorithm),
+
+    #[cfg(feature = "jwt-convert")]
+    #[error("`{0}` is a JWE algorithm, not a JWS algorithm")]
+    NotSigningAlgorithm(KeyManagementAlgorithm),
 }
@@ -0,0 +1,288 @@
+use yasna::{models::ObjectIdentifier, ASN1Error, ASN1ErrorKind, ASN1Result, BERReader, Tag};
+
+use crate::{ByteVec, ConversionError, Curve, Key, OkpCurve, RsaPrivate, RsaPublic};
+
+const RSA_ENCRYPTION_OID: &[u64] = &[1, 2, 840, 113549, 1, 1, 1];
+const EC_PUBLIC_KEY_OID: &[u64] = &[1, 2, 840, 10045, 2, 1];
+
+type Decoder = fn(&[u8]) -> Result<Key, ConversionError>;
+
+impl Key {
+    /// Decodes a DER-encoded PKCS#8 or SEC1 private key, or an SPKI or PKCS#1 public key.
+    /// PKCS#1 private keys are also accepted. With the `generate` feature, a missing public key
+    /// is derived from the private key, except for Ed448 and X448.
+    pub fn from_der(der: impl AsRef<[u8]>) -> Result<Self, ConversionError> {
+        let der = der.as_ref();
+        let decoders: [Decoder; 5] = [
+            decode_pkcs8,
+            decode_spki,
+            |der| decode_sec1(der, None),
+            decode_pkcs1_private,
+            decode_pkcs1_public,
+        ];
+        let mut result = Err(invalid_der());
+        for decode in decoders {
+            result = decode(der);
+            // Only try the next format if this one didn't even parse.
+            if !matches!(result, Err(ConversionError::Der(_))) {
+                break;
+            }
+        }
+        result
+    }
+
+    /// Decodes a PEM-armored key. The following labels are recognized:
+    /// * `PRIVATE KEY` (PKCS#8)
+    /// * `PUBLIC KEY` (SPKI)
+    /// * `RSA PRIVATE KEY` and `RSA PUBLIC KEY` (PKCS#1)
+    /// * `EC PRIVATE KEY` (SEC1)
+    ///
+    /// As in OpenSSL's output, text before a block (e.g., `Bag Attributes`) and blocks with
+    /// other labels (e.g., `EC PARAMETERS`) are skipped, and the first key is decoded.
+    pub fn from_pem(pem: impl AsRef<str>) -> Result<Self, ConversionError> {
+        let mut lines = pem.as_ref().lines().map(str::trim);
+        let mut unsupported_label = None;
+        while let Some((label, der)) = next_pem_block(&mut lines)? {
+            let decode: Decoder = match label {
+                "PRIVATE KEY" => decode_pkcs8,
+                "PUBLIC KEY" => decode_spki,
+                "RSA PRIVATE KEY" => decode_pkcs1_private,
+                "RSA PUBLIC KEY" => decode_pkcs1_public,
+                "EC PRIVATE KEY" => |der| decode_sec1(der, None),
+                _ => {
+                    unsupported_label.get_or_insert(label);
+                    continue;
+                }
+            };
+            return decode(&der);
+        }
+        Err(match unsupported_label {
+            Some(label) => ConversionError::UnsupportedPemLabel(label.into()),
+            None => ConversionError::Pem,
+        })
+    }
+}
+
+fn invalid_der() -> ConversionError {
+    ConversionError::Der(ASN1Error::new(ASN1ErrorKind::Invalid))
+}
+
+/// Returns the label and contents of the next PEM block, skipping any text before it.
+fn next_pem_block<'a>(
+    lines: &mut impl Iterator<Item = &'a str>,
+) -> Result<Option<(&'a str, Vec<u8>)>, ConversionError> {
+    let label = match lines.find_map(|l| l.strip_prefix("-----BEGIN ")) {
+        Some(label) => label.strip_suffix("-----").ok_or(ConversionError::Pem)?,
+        None => return Ok(None),
+    };
+    let end = format!("-----END {}-----", label);
+    let mut body = String::new();
+    for line in lines {
+        if line == end {
+            return base64::decode(&body)
+                .map(|der| Some((label, der)))
+                .map_err(|_| ConversionError::Pem);
+        }
+        if line.contains(':') {
+            // Encrypted PEM headers (e.g., `Proc-Type: 4,ENCRYPTED`) aren't supported.
+            return Err(ConversionError::Pem);
+        }
+        body.push_str(line);
+    }
+    Err(ConversionError::Pem)
+}
+
+/// Reads an `AlgorithmIdentifier`, returning the algorithm OID and the DER-encoded parameters.
+fn read_algorithm_identifier(
+    reader: BERReader<'_, '_>,
+) -> ASN1Result<(ObjectIdentifier, Option<Vec<u8>>)> {
+    reader.read_sequence(|reader| {
+        let algorithm = reader.next().read_oid()?;
+        let params = reader.read_optional(|reader| reader.read_der())?;
+        Ok((algorithm, params))
+    })
+}
+
+fn read_curve(params: Option<&[u8]>) -> Result<Curve, ConversionError> {
+    let oid = yasna::parse_der(params.ok_or_else(invalid_der)?, |reader| reader.read_oid())?;
+    Curve::from_oid(&oid).ok_or(ConversionError::UnsupportedCurve(oid))
+}
+
+/// https://tools.ietf.org/html/rfc5958#section-2
+fn decode_pkcs8(der: &[u8]) -> Result<Key, ConversionError> {
+    let (algorithm, params, private_key, public_key) = yasna::parse_der(der, |reader| {
+        reader.read_sequence(|reader| {
+            if reader.next().read_u8()? > 1 {
+                return Err(ASN1Error::new(ASN1ErrorKind::Invalid)); // unknown version
+            }
+            let (algorithm, params) = read_algorithm_identifier(reader.next())?;
+            let private_key = reader.next().read_bytes()?;
+            let mut public_key = None;
+            while let Some(value) = reader.read_optional(|reader| reader.read_tagged_der())? {
+                // [0] is attributes, which are ignored. [1] is the (implicitly tagged) BIT STRING.
+                if value.tag() == Tag::context(1) {
+                    match value.value().split_first() {
+                        Some((0 /* unused bits */, bytes)) => public_key = Some(bytes.to_vec()),
+                        _ => return Err(ASN1Error::new(ASN1ErrorKind::Invalid)),
+                    }
+                }
+            }
+            Ok((algorithm, params, private_key, public_key))
+        })
+    })?;
+    match algorithm.components().as_slice() {
+        RSA_ENCRYPTION_OID => decode_pkcs1_private(&private_key),
+        EC_PUBLIC_KEY_OID => decode_sec1(&private_key, Some(read_curve(params.as_deref())?)),
+        _ => {
+            let curve = OkpCurve::from_oid(&algorithm)
+                .ok_or(ConversionError::UnsupportedKeyAlgorithm(algorithm))?;
+            // https://tools.ietf.org/html/rfc8410#section-7
+            let d = yasna::parse_der(&private_key, |reader| reader.read_bytes())?;
+            match public_key {
+                Some(x) => okp_key(curve, Some(d), x),
+                #[cfg(feature = "generate")]
+                None if matches!(curve, OkpCurve::Ed25519 | OkpCurve::X25519) => {
+                    Key::from_okp_private_key(curve, d).map_err(|_| invalid_der())
+                }
+                None => Err(ConversionError::MissingPublicKey),
+            }
+        }
+    }
+}
+
+/// https://tools.ietf.org/html/rfc5280#section-4.1.2.7
+fn decode_spki(der: &[u8]) -> Result<Key, ConversionError> {
+    let (algorithm, params, public_key) = yasna::parse_der(der, |reader| {
+        reader.read_sequence(|reader| {
+            let (algorithm, params) = read_algorithm_identifier(reader.next())?;
+            let (public_key, num_bits) = reader.next().read_bitvec_bytes()?;
+            if num_bits % 8 != 0 {
+                return Err(ASN1Error::new(ASN1ErrorKind::Invalid));
+            }
+            Ok((algorithm, params, public_key))
+        })
+    })?;
+    match algorithm.components().as_slice() {
+        RSA_ENCRYPTION_OID => decode_pkcs1_public(&public_key),
+        EC_PUBLIC_KEY_OID => ec_key(read_curve(params.as_deref())?, None, &public_key),
+        _ => {
+            let curve = OkpCurve::from_oid(&algorithm)
+                .ok_or(ConversionError::UnsupportedKeyAlgorithm(algorithm))?;
+            okp_key(curve, None, public_key)
+        }
+    }
+}
+
+/// https://tools.ietf.org/html/rfc5915#section-3
+fn decode_sec1(der: &[u8], curve: Option<Curve>) -> Result<Key, ConversionError> {
+    let (d, params, public_key) = yasna::parse_der(der, |reader| {
+        reader.read_sequence(|reader| {
+            if reader.next().read_u8()? != 1 {
+                return Err(ASN1Error::new(ASN1ErrorKind::Invalid)); // unknown version
+            }
+            let d = reader.next().read_bytes()?;
+            let params = reader.read_optional(|reader| {
+                reader.read_tagged(Tag::context(0), |reader| reader.read_oid())
+            })?;
+            let public_key = reader.read_optional(|reader| {
+                reader.read_tagged(Tag::context(1), |reader| reader.read_bitvec_bytes())
+            })?;
+            Ok((d, params, public_key.map(|(bytes, _)| bytes)))
+        })
+    })?;
+    let curve = match (curve, params) {
+        (Some(curve), _) => curve,
+        (None, Some(oid)) => Curve::from_oid(&oid).ok_or(ConversionError::UnsupportedCurve(oid))?,
+        (None, None) => return Err(invalid_der()),
+    };
+    match public_key {
+        Some(public_key) => ec_key(curve, Some(d), &public_key),
+        // The public key is optional, but can be derived.
+        #[cfg(feature = "generate")]
+        None => Key::from_ec_private_key(curve, d).map_err(|_| invalid_der()),
+        #[cfg(not(feature = "generate"))]
+        None => Err(ConversionError::MissingPublicKey),
+    }
+}
+
+/// https://tools.ietf.org/html/rfc8017#appendix-A.1.2
+fn decode_pkcs1_private(der: &[u8]) -> Result<Key, ConversionError> {
+    Ok(yasna::parse_der(der, |reader| {
+        reader.read_sequence(|reader| {
+            if reader.next().read_u8()? != 0 {
+                // Multi-prime keys aren't supported.
+                return Err(ASN1Error::new(ASN1ErrorKind::Invalid));
+            }
+            let mut read_uint = || reader.next().read_biguint().map(|n| n.to_bytes_be().into());
+            Ok(Key::RSA {
+                public: RsaPublic {
+                    n: read_uint()?,
+                    e: read_uint()?,
+                },
+                private: Some(RsaPrivate {
+                    d: read_uint()?,
+                    p: Some(read_uint()?),
+                    q: Some(read_uint()?),
+                    dp: Some(read_uint()?),
+                    dq: Some(read_uint()?),
+                    qi: Some(read_uint()?),
+                }),
+            })
+        })
+    })?)
+}
+
+/// https://tools.ietf.org/html/rfc8017#appendix-A.1.1
+fn decode_pkcs1_public(der: &[u8]) -> Result<Key, ConversionError> {
+    Ok(yasna::parse_der(der, |reader| {
+        reader.read_sequence(|reader| {
+            let mut read_uint = || reader.next().read_biguint().map(|n| n.to_bytes_be().into());
+            Ok(Key::RSA {
+                public: RsaPublic {
+                    n: read_uint()?,
+                    e: read_uint()?,
+                },
+                private: None,
+            })
+        })
+    })?)
+}
+
+/// Creates an EC key from the private scalar and the SEC1-encoded uncompressed public point.
+fn ec_key(curve: Curve, d: Option<Vec<u8>>, point: &[u8]) -> Result<Key, ConversionError> {
+    let len = curve.coordinate_len();
+    let (x, y) = match point.split_first() {
+        Some((0x04 /* uncompressed */, coords)) if coords.len() == 2 * len => coords.split_at(len),
+        _ => return Err(invalid_der()),
+    };
+    let d = match d {
+        // Some encoders strip the leading zeros of the private scalar.
+        Some(d) if d.len() <= len => {
+            let mut padded = vec![0; len - d.len()];
+            padded.extend_from_slice(&d);
+            Some(ByteVec::from(padded))
+        }
+        Some(_) => return Err(invalid_der()),
+        None => None,
+    };
+    Ok(Key::EC {
+        curve,
+        d,
+        x: x.into(),
+        y: y.into(),
+    })
+}
+
+fn okp_key(curve: OkpCurve, d: Option<Vec<u8>>, x: Vec<u8>) -> Result<Key, ConversionError> {
+    if x.len() != curve.public_key_len()
+        || d.as_ref()
+            .is_some_and(|d| d.len() != curve.private_key_len())
+    {
+        return Err(invalid_der());
+    }
+    Ok(Key::OKP {
+        curve,
+        d: d.map(Into::into),
+        x: x.into(),
+    })
+}
@@ -0,0 +1,129 @@
+use num_bigint::BigUint;
+use num_integer::Integer;
+
+use crate::{ByteVec, ConversionError, Key, RsaPrivate, RsaPublic};
+
+/// The number of bases tried when factoring the modulus. Each succeeds with probability
+/// at least 1/2, so a failure means that the parameters are inconsistent.
+const MAX_FACTORING_ATTEMPTS: u32 = 100;
+
+impl Key {
+    /// Creates an RSA private key from the public exponent and the two prime factors,
+    /// computing the modulus, the private exponent, and the CRT parameters.
+    pub fn from_rsa_primes(
+        e: impl AsRef<[u8]>,
+        p: impl AsRef<[u8]>,
+        q: impl AsRef<[u8]>,
+    ) -> Result<Self, ConversionError> {
+        let e = BigUint::from_bytes_be(e.as_ref());
+        let p = BigUint::from_bytes_be(p.as_ref());
+        let q = BigUint::from_bytes_be(q.as_ref());
+        let one = BigUint::from(1u8);
+        if p <= one || q <= one || p == q {
+            return Err(ConversionError::InvalidRsaParams);
+        }
+        let n = &p * &q;
+        let lambda = (&p - 1u8).lcm(&(&q - 1u8));
+        let d = e.modinv(&lambda).ok_or(ConversionError::InvalidRsaParams)?;
+        let public = RsaPublic {
+            e: e.to_bytes_be().into(),
+            n: n.to_bytes_be().into(),
+        };
+        let mut private = RsaPrivate {
+            d: d.to_bytes_be().into(),
+            p: Some(p.to_bytes_be().into()),
+            q: Some(q.to_bytes_be().into()),
+            dp: None,
+            dq: None,
+            qi: None,
+        };
+        private.complete(&public)?;
+        Ok(Self::RSA {
+            public,
+            private: Some(private),
+        })
+    }
+}
+
+impl RsaPrivate {
+    /// Fills in whichever of `p`, `q`, `dp`, `dq`, and `qi` are missing.
+    /// When neither prime is known, they're recovered from `n`, `e`, and `d` as per
+    /// [NIST SP 800-56B Appendix C](https://doi.org/10.6028/NIST.SP.800-56Br2).
+    ///
+    /// Keys that specify only `d` are legal; `Key::try_to_der` completes them as needed.
+    pub fn complete(&mut self, public: &RsaPublic) -> Result<(), ConversionError> {
+        let n = BigUint::from_bytes_be(&public.n);
+        let e = BigUint::from_bytes_be(&public.e);
+        let d = BigUint::from_bytes_be(&self.d);
+        let uint = |param: &Option<ByteVec>| param.as_ref().map(|p| BigUint::from_bytes_be(p));
+
+        let (p, q) = match (uint(&self.p), uint(&self.q)) {
+            (Some(p), Some(q)) => (p, q),
+            (Some(p), None) => (p.clone(), cofactor(&n, &p)?),
+            (None, Some(q)) => (cofactor(&n, &q)?, q),
+            (None, None) => factor(&n, &e, &d)?,
+        };
+        let one = BigUint::from(1u8);
+        if p <= one || q <= one || &p * &q != n {
+            return Err(ConversionError::InvalidRsaParams);
+        }
+
+        let dp = uint(&self.dp).unwrap_or_else(|| &d % (&p - 1u8));
+        let dq = uint(&self.dq).unwrap_or_else(|| &d % (&q - 1u8));
+        let qi = match uint(&self.qi) {
+            Some(qi) => qi,
+            None => q.modinv(&p).ok_or(ConversionError::InvalidRsaParams)?,
+        };
+        self.p = Some(p.to_bytes_be().into());
+        self.q = Some(q.to_bytes_be().into());
+        self.dp = Some(dp.to_bytes_be().into());
+        self.dq = Some(dq.to_bytes_be().into());
+        self.qi = Some(qi.to_bytes_be().into());
+        Ok(())
+    }
+}
+
+fn cofactor(n: &BigUint, p: &BigUint) -> Result<BigUint, ConversionError> {
+    if *p <= BigUint::from(1u8) {
+        return Err(ConversionError::InvalidRsaParams);
+    }
+    let (q, rem) = n.div_rem(p);
+    if rem != BigUint::default() {
+        return Err(ConversionError::InvalidRsaParams);
+    }
+    Ok(q)
+}
+
+/// Factors `n` into `(p, q)` with `p > q`, using that `e * d - 1` is a multiple of
+/// `lcm(p - 1, q - 1)`, so a nontrivial square root of 1 mod `n` can be found from it.
+fn factor(n: &BigUint, e: &BigUint, d: &BigUint) -> Result<(BigUint, BigUint), ConversionError> {
+    let one = BigUint::from(1u8);
+    let ed = e * d;
+    if *n <= one || ed <= one || ed.is_even() {
+        return Err(ConversionError::InvalidRsaParams);
+    }
+    let k = ed - &one;
+    let t = k.trailing_zeros().unwrap_or_default();
+    let r = &k >> t;
+    let n_minus_one = n - &one;
+
+    for g in 2..2 + MAX_FACTORING_ATTEMPTS {
+        let mut y = BigUint::from(g).modpow(&r, n);
+        if y == one || y == n_minus_one {
+            continue;
+        }
+        for _ in 0..t {
+            let x = y.modpow(&BigUint::from(2u8), n);
+            if x == one {
+                let p = (&y - &one).gcd(n);
+                let q = n / &p;
+                return Ok(if p > q { (p, q) } else { (q, p) });
+            }
+            if x == n_minus_one {
+                break;
+            }
+            y = x;
+        }
+    }
+    Err(ConversionError::InvalidRsaParams)
+}
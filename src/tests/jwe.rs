@@ -0,0 +1,426 @@
+use super::*;
+use crate::jwe::{self, EncryptionAlgorithm as Enc, KeyManagementAlgorithm as Alg};
+
+static PLAINTEXT: &[u8] = b"The true sign of intelligence is not knowledge but imagination.";
+
+// Generated using Python's `cryptography` package.
+static X25519_JWK_FIXTURE: &str = r#"{
+        "kty": "OKP",
+        "crv": "X25519",
+        "d": "eEU3Y93WOz-69gpfEJbUK4NZxg6IlAL11CB4yULCd2w",
+        "x": "1rJWDkT5ShYknuO7R2OPxClwBjZAYfwEYHlTIraItwE"
+    }"#;
+
+// From https://tools.ietf.org/html/rfc7518#appendix-C
+static ECDH_ES_JWK_FIXTURE: &str = r#"{
+        "kty": "EC",
+        "crv": "P-256",
+        "x": "weNJy2HscCSM6AEDTDg04biOvhFhyyWvOHQfeF_PxMQ",
+        "y": "e8lnCO-AlStT-NJVX-crhB7QRYhiix03illJOVAOyck",
+        "d": "VEmDZpDXXK8p8N0Cndsxs924q6nS1RXFASRl6BfUqdw"
+    }"#;
+
+/// Returns the key in `fixture` without its `alg`, `use`, and `key_ops`.
+fn key(fixture: &str) -> JsonWebKey {
+    JsonWebKey::new(*JsonWebKey::from_str(fixture).unwrap().key)
+}
+
+fn oct(len: usize) -> JsonWebKey {
+    JsonWebKey::new(Key::Symmetric {
+        key: vec![42; len].into(),
+    })
+}
+
+#[test]
+fn rfc7516_a128kw() {
+    // From https://tools.ietf.org/html/rfc7516#appendix-A.3
+    let jwk = JsonWebKey::from_str(r#"{"kty":"oct","k":"GawgguFyGrWKav7AX4VKUg"}"#).unwrap();
+    let token = "eyJhbGciOiJBMTI4S1ciLCJlbmMiOiJBMTI4Q0JDLUhTMjU2In0.6KB707dM9YTIgHtLvtgWQ8mKwboJW3of9locizkDTHzBC2IlrT1oOQ.AxY8DCtDaGlsbGljb3RoZQ.KDlTtXchhZTGufMYmOYGS4HffxPSUrfmqCHXaI9wOGY.U0m_YmjN04DJvceFICbCVQ";
+    let decrypted = jwe::decrypt(token, &jwk).unwrap();
+    assert_eq!(
+        decrypted.header,
+        jwe::Header::new(Alg::A128KW, Enc::A128CbcHs256)
+    );
+    assert_eq!(decrypted.plaintext, b"Live long and prosper.");
+}
+
+#[test]
+fn rfc7518_ecdh_es_kdf() {
+    // From https://tools.ietf.org/html/rfc7518#appendix-C
+    let mut header = jwe::Header::new(Alg::EcdhEs, Enc::A128GCM);
+    header.party_u_info = Some(b"Alice".to_vec().into());
+    header.party_v_info = Some(b"Bob".to_vec().into());
+    let shared_secret = [
+        158, 86, 217, 29, 129, 113, 53, 211, 114, 131, 66, 131, 191, 132, 38, 156, 251, 49, 110,
+        163, 218, 128, 106, 72, 246, 218, 167, 121, 140, 254, 144, 196,
+    ];
+    assert_eq!(
+        *jwe::key_management::concat_kdf(&header, &shared_secret),
+        [86, 170, 141, 234, 248, 35, 109, 32, 92, 34, 40, 205, 113, 167, 16, 26]
+    );
+}
+
+#[test]
+fn decrypt_external() {
+    // Encrypted using Python's `cryptography` package.
+    let cases = [
+        (RSA_2048_JWK_FIXTURE, "eyJhbGciOiJSU0EtT0FFUC0yNTYiLCJlbmMiOiJBMjU2R0NNIn0.fEW2VAxG5nGt_fY4A_bqV5EVt3cqmvHJUtrgP0hxOzFWbTlu3iLnONQNik_pJcYbizb2YZ2zhxKW1JxZioAXsuyhNY8I6jg6x-xPTUpSeEFHGTH_Fo2QVU-M3s04nvG6_GdElh7uo6zscV6kt04Gx7lI47HpLnOmqwed4YyMCQ5IR82fx9UzLdT8XFmxjg5tPnLzb0wBOCx1zoAqDuJ1rbqCjBO33mVWBT89l65jfeA_45PNyJW-cDBGzEvd2JHlOCoO160bhui0A7FVwN6C-jlvI-eXKSX3GgZNHuef9Xi4x1x0TyFIYhmmiQzVGsxO82ldTxjf-TICUep7zmloBQ.ZZz4EQNMqCqEdFji.MT9j-5TBh3wf1ZjpnP1MFC-BlOvkHhS_p4Z_j07qxSZVWgR_q6Sr6OugIKEiY3ASPxZ86XPzp-ph20k1zu42.s1QwmKFhggKWe78Q38EMww"),
+        (RSA_2048_JWK_FIXTURE, "eyJhbGciOiJSU0EtT0FFUCIsImVuYyI6IkExMjhDQkMtSFMyNTYifQ.FoD-V-fuRyC69XtXjNjfY_OIK225jRlV3W0W3yHd54VecHLWS_zyUNBPLW-lFDRnrN1SPI0YtjA1nYL2_AF82NyRH8O87hFOMBSvLSlg-3MsJQHCr1KYSUw63MEUxJDJnMrpTvXOGy-VXboShAuO70FLpwmo-gwoCWov8HJJsNCfqdXXPUQbuPrIJFvQCpKslqZUycCZ_UBdqrXFO-ROxH2AML0IT-tSi_wVcoeJBv70CnAT00_N4utZT6_fe-qj3JA5D1OGMXLgtpayp7bk6kk0swXzeppUBuUfymMlaAgN6lVX_4t9oorUNVvaxjA2-8eMxX0-Clm1TgdeyjNXBw.BwLPgxYFwgbMge3eNKcXkQ.1cpXi_qoPngSLp23VhV-ipBlavJY2vYSEa07NfAvuv70M6RD_rsn1NzOsOwn6J7fgFuy6ZArzw16g9srtz8Fng.Ae5hBmVXZ6sO01IFrhHGfw"),
+        (ECDH_ES_JWK_FIXTURE, "eyJhbGciOiJFQ0RILUVTIiwiZW5jIjoiQTEyOEdDTSIsImFwdSI6IlFXeHBZMlUiLCJhcHYiOiJRbTlpIiwiZXBrIjp7Imt0eSI6IkVDIiwiY3J2IjoiUC0yNTYiLCJ4IjoiZ0kwR0FJTEJkdTdUNTNha3JGbU15R2NzRjNuNWRPN01td05CSEtXNVNWMCIsInkiOiJTTFdfeFNmZnpsUFdySEVWSTMwREhNXzRlZ1Z3dDNOUXFlVUQ3bk1GcHBzIn19..mdcfmOwORRFKVQTo.bm5vH4faEjJCEIH5H8T_7Jw0Wl8TvC4ozi4uxrJNqimRgLTIXMK3lCqACbeLMs4HpPk2vPa0s9w_LzoOBmEc.oiAuDvls1i1l8v39CkNaGg"),
+        (X25519_JWK_FIXTURE, "eyJhbGciOiJFQ0RILUVTK0EyNTZLVyIsImVuYyI6IkExMjhHQ00iLCJlcGsiOnsia3R5IjoiT0tQIiwiY3J2IjoiWDI1NTE5IiwieCI6IkwzNGFvQzNNUUZRRDRuMkMtN0Nxdm5yVTkzWjdvMGRhYTNVVlBSQnRuZ00ifX0.NlQCisauYNKTh_iWQnjfoZIsSlF9PtFY.ghuHJnyeD6nZqkiX.JgbneEl-jTzf2S0NS0CA7uNm7jzZRUBIlxS3ydGzIGLweUWfVSwxwtgJXJpnwipm3_eazvtis2MGPXaHirBr.nZrrV9N-PSK9Z2HJOuOpdQ"),
+    ];
+    for (fixture, token) in cases {
+        let decrypted = jwe::decrypt(token, &key(fixture)).unwrap();
+        assert_eq!(decrypted.plaintext, PLAINTEXT);
+    }
+}
+
+#[test]
+fn encrypt_and_decrypt() {
+    let cases = [
+        (key(RSA_2048_JWK_FIXTURE), Alg::RsaOaep),
+        (key(RSA_2048_JWK_FIXTURE), Alg::RsaOaep256),
+        (oct(16), Alg::A128KW),
+        (oct(32), Alg::A256KW),
+        (key(P256_JWK_FIXTURE), Alg::EcdhEs),
+        (key(P384_JWK_FIXTURE), Alg::EcdhEsA128KW),
+        (key(P521_JWK_FIXTURE), Alg::EcdhEsA256KW),
+        (key(X25519_JWK_FIXTURE), Alg::EcdhEs),
+        (key(X25519_JWK_FIXTURE), Alg::EcdhEsA256KW),
+    ];
+    for (jwk, alg) in cases {
+        let public_jwk = match jwk.key.to_public() {
+            Some(public_key) => JsonWebKey::new(public_key.into_owned()),
+            None => jwk.clone(),
+        };
+        for enc in [Enc::A128CbcHs256, Enc::A128GCM, Enc::A256GCM] {
+            let mut header = jwe::Header::new(alg, enc);
+            header.key_id = Some("recipient".into());
+            let token = jwe::encrypt(&header, PLAINTEXT, &public_jwk).unwrap();
+
+            let decoded_header = jwe::decode_header(&token).unwrap();
+            assert_eq!(decoded_header.key_id.as_deref(), Some("recipient"));
+            assert_eq!(
+                decoded_header.ephemeral_key.is_some(),
+                matches!(alg, Alg::EcdhEs | Alg::EcdhEsA128KW | Alg::EcdhEsA256KW)
+            );
+            let decrypted = jwe::decrypt(&token, &jwk).unwrap();
+            assert_eq!(decrypted.header, decoded_header);
+            assert_eq!(decrypted.plaintext, PLAINTEXT);
+
+            // Flip a bit of the ciphertext.
+            let mut parts: Vec<_> = token.split('.').map(String::from).collect();
+            let mut ciphertext = base64::decode_config(&parts[3], base64::URL_SAFE_NO_PAD).unwrap();
+            ciphertext[0] ^= 1;
+            parts[3] = base64::encode_config(ciphertext, base64::URL_SAFE_NO_PAD);
+            assert!(matches!(
+                jwe::decrypt(&parts.join("."), &jwk),
+                Err(jwe::Error::DecryptionFailed)
+            ));
+        }
+    }
+
+    for enc in [Enc::A128CbcHs256, Enc::A128GCM, Enc::A256GCM] {
+        let jwk = oct(enc.key_len());
+        let token = jwe::encrypt(&jwe::Header::new(Alg::Direct, enc), PLAINTEXT, &jwk).unwrap();
+        assert!(token.contains(".."));
+        assert_eq!(jwe::decrypt(&token, &jwk).unwrap().plaintext, PLAINTEXT);
+    }
+}
+
+#[test]
+fn key_management_alg() {
+    let mut json: serde_json::Value = serde_json::from_str(RSA_2048_JWK_FIXTURE).unwrap();
+    json["alg"] = "RSA-OAEP-256".into();
+    let jwk = JsonWebKey::from_str(&json.to_string()).unwrap();
+    assert_eq!(jwk.algorithm, Some(Alg::RsaOaep256.into()));
+    assert_eq!(serde_json::to_value(&jwk).unwrap()["alg"], "RSA-OAEP-256");
+
+    let jwks: JwkSet = serde_json::from_value(serde_json::json!({ "keys": [json] })).unwrap();
+    assert_eq!(jwks.keys().next(), Some(&jwk));
+
+    let header = jwe::Header::new(Alg::RsaOaep256, Enc::A128GCM);
+    let token = jwe::encrypt(&header, PLAINTEXT, &jwk).unwrap();
+    assert_eq!(jwe::decrypt(&token, &jwk).unwrap().plaintext, PLAINTEXT);
+    assert!(matches!(
+        jwe::encrypt(
+            &jwe::Header::new(Alg::RsaOaep, Enc::A128GCM),
+            PLAINTEXT,
+            &jwk
+        ),
+        Err(jwe::Error::AlgorithmNotPermitted(
+            KeyAlgorithm::KeyManagement(Alg::RsaOaep256)
+        ))
+    ));
+
+    json["alg"] = "A128KW".into();
+    assert!(matches!(
+        JsonWebKey::from_str(&json.to_string()),
+        Err(Error::MismatchedAlgorithm)
+    ));
+    let mut jwk = oct(16);
+    jwk.set_algorithm(Alg::A128KW).unwrap();
+    assert!(jwk.set_algorithm(Alg::RsaOaep).is_err());
+}
+
+#[test]
+fn key_restrictions() {
+    let header = jwe::Header::new(Alg::RsaOaep, Enc::A128GCM);
+
+    // The fixture is an encryption key that only permits `wrapKey`.
+    let jwk = JsonWebKey::from_str(RSA_JWK_FIXTURE).unwrap();
+    let token = jwe::encrypt(&header, PLAINTEXT, &jwk).unwrap();
+    assert!(matches!(
+        jwe::decrypt(&token, &jwk),
+        Err(jwe::Error::OperationNotPermitted(ops)) if ops == KeyOps::UNWRAP_KEY
+    ));
+
+    let mut jwk = key(RSA_2048_JWK_FIXTURE);
+    jwk.key_use = Some(KeyUse::Signing);
+    assert!(matches!(
+        jwe::encrypt(&header, PLAINTEXT, &jwk),
+        Err(jwe::Error::OperationNotPermitted(ops)) if ops == KeyOps::WRAP_KEY
+    ));
+    jwk.key_use = None;
+    jwk.algorithm = Some(Algorithm::RS256.into());
+    assert!(matches!(
+        jwe::encrypt(&header, PLAINTEXT, &jwk),
+        Err(jwe::Error::AlgorithmNotPermitted(KeyAlgorithm::Signing(
+            Algorithm::RS256
+        )))
+    ));
+
+    let jwk = key(RSA_2048_JWK_FIXTURE);
+    let token = jwe::encrypt(&header, PLAINTEXT, &jwk).unwrap();
+    let public_jwk = JsonWebKey::new(jwk.key.to_public().unwrap().into_owned());
+    assert!(matches!(
+        jwe::decrypt(&token, &public_jwk),
+        Err(jwe::Error::OperationNotPermitted(ops)) if ops == KeyOps::UNWRAP_KEY
+    ));
+    assert!(matches!(
+        jwe::decrypt(&token, &oct(16)),
+        Err(jwe::Error::Key(Error::MismatchedAlgorithm))
+    ));
+
+    let direct = jwe::Header::new(Alg::Direct, Enc::A256GCM);
+    assert!(matches!(
+        jwe::encrypt(&direct, PLAINTEXT, &oct(16)),
+        Err(jwe::Error::InvalidKey)
+    ));
+    assert!(matches!(
+        jwe::encrypt(
+            &jwe::Header::new(Alg::A256KW, Enc::A128GCM),
+            PLAINTEXT,
+            &oct(16)
+        ),
+        Err(jwe::Error::InvalidKey)
+    ));
+    assert!(matches!(
+        jwe::encrypt(
+            &jwe::Header::new(Alg::EcdhEs, Enc::A128GCM),
+            PLAINTEXT,
+            &key(SECP256K1_JWK_FIXTURE)
+        ),
+        Err(jwe::Error::UnsupportedCurve("secp256k1"))
+    ));
+}
+
+#[test]
+fn malformed() {
+    let jwk = oct(16);
+    let header = jwe::Header::new(Alg::A128KW, Enc::A128GCM);
+    let token = jwe::encrypt(&header, PLAINTEXT, &jwk).unwrap();
+    let (_, rest) = token.split_once('.').unwrap();
+
+    assert!(matches!(
+        jwe::decrypt(rest, &jwk),
+        Err(jwe::Error::Malformed)
+    ));
+    assert!(matches!(
+        jwe::decrypt(&format!("{}.", token), &jwk),
+        Err(jwe::Error::Malformed)
+    ));
+
+    let with_header = |header: &str| {
+        format!(
+            "{}.{}",
+            base64::encode_config(header, base64::URL_SAFE_NO_PAD),
+            rest
+        )
+    };
+    // Changing the header invalidates the authentication tag.
+    assert!(matches!(
+        jwe::decrypt(
+            &with_header(r#"{"alg":"A128KW","enc":"A128GCM","kid":"a"}"#),
+            &jwk
+        ),
+        Err(jwe::Error::DecryptionFailed)
+    ));
+    assert!(matches!(
+        jwe::decrypt(&with_header(r#"{"alg":"A128KW","enc":"A128GCM","crit":["exp"],"exp":0}"#), &jwk),
+        Err(jwe::Error::UnsupportedCriticalParam(param)) if param == "exp"
+    ));
+    assert!(matches!(
+        jwe::decrypt(
+            &with_header(r#"{"alg":"A128KW","enc":"A128GCM","zip":"DEF"}"#),
+            &jwk
+        ),
+        Err(jwe::Error::UnsupportedCompression)
+    ));
+    assert!(matches!(
+        jwe::decrypt(&with_header(r#"{"alg":"RSA1_5","enc":"A128GCM"}"#), &jwk),
+        Err(jwe::Error::Header(_))
+    ));
+
+    let jwk = key(P256_JWK_FIXTURE);
+    let token = jwe::encrypt(
+        &jwe::Header::new(Alg::EcdhEs, Enc::A128GCM),
+        PLAINTEXT,
+        &jwk,
+    )
+    .unwrap();
+    let (_, rest) = token.split_once('.').unwrap();
+    let without_epk = format!(
+        "{}.{}",
+        base64::encode_config(
+            r#"{"alg":"ECDH-ES","enc":"A128GCM"}"#,
+            base64::URL_SAFE_NO_PAD
+        ),
+        rest
+    );
+    assert!(matches!(
+        jwe::decrypt(&without_epk, &jwk),
+        Err(jwe::Error::InvalidEphemeralKey)
+    ));
+    assert!(matches!(
+        jwe::decrypt(&token, &key(X25519_JWK_FIXTURE)),
+        Err(jwe::Error::InvalidEphemeralKey)
+    ));
+}
+
+#[test]
+fn decrypt_external_jwk() {
+    // Encrypted using Python's `cryptography` package.
+    let token = "eyJhbGciOiJQQkVTMi1IUzI1NitBMTI4S1ciLCJwMnMiOiJoNXpwbG9ndmVVN1gyX2dybDRxbEtnIiwicDJjIjo0MDk2LCJlbmMiOiJBMTI4Q0JDLUhTMjU2IiwiY3R5IjoiandrK2pzb24ifQ.b4yq0mmVojjBfK97_oQmz0fYNycWvvzq1t3qcIVsM6FZksZLZK4qDA.iPATIXUn5CstVoxsBRWfzQ.Gyqo1pt1s5br2Pfp_9tH6SJUqXWTy_muL424aGDhb2Oh4M7LjQG1bs8qQdU1uKr0d7SyOv3Q9f-3zZjPyJh0PvYM-mLTDilB4BGOE2NDeN4Hdn54DHVhf8k77ZscDigP6aL3zgyiS6pX5Ev4aPeqUIdOs1EDqvV0LLsJe7L48QtAjRMMKb3MpF7ojsSJRLvg.ak7YdytROlZwJxBeROGnIw";
+    let password = "Thus from my lips, by yours, my sin is purged.";
+    assert_eq!(
+        JsonWebKey::decrypt_with_password(token, password).unwrap(),
+        JsonWebKey::from_str(ED25519_JWK_FIXTURE).unwrap()
+    );
+    assert!(matches!(
+        JsonWebKey::decrypt_with_password(token, "password"),
+        Err(jwe::Error::DecryptionFailed)
+    ));
+    assert!(matches!(
+        JsonWebKey::decrypt_with_key(token, &oct(16)),
+        Err(jwe::Error::Key(Error::MismatchedAlgorithm))
+    ));
+}
+
+#[test]
+fn encrypted_jwk() {
+    let jwk = JsonWebKey::from_str(RSA_JWK_FIXTURE).unwrap();
+
+    let token = jwk.encrypt_with_password("hunter2").unwrap();
+    let header = jwe::decode_header(&token).unwrap();
+    assert_eq!(header.algorithm, Alg::Pbes2Hs256A128KW);
+    assert_eq!(header.encryption, Enc::A128CbcHs256);
+    assert_eq!(header.content_type.as_deref(), Some("jwk+json"));
+    assert_eq!(header.pbes2_count, Some(jwe::DEFAULT_PBES2_COUNT));
+    assert_eq!(header.pbes2_salt.unwrap().len(), 16);
+    assert_eq!(
+        JsonWebKey::decrypt_with_password(&token, "hunter2").unwrap(),
+        jwk
+    );
+
+    let mut wrapping_key = oct(32);
+    wrapping_key.key_id = Some("storage".into());
+    let token = jwk.encrypt_with_key(Alg::A256KW, &wrapping_key).unwrap();
+    assert_eq!(
+        jwe::decode_header(&token).unwrap().key_id.as_deref(),
+        Some("storage")
+    );
+    assert_eq!(
+        JsonWebKey::decrypt_with_key(&token, &wrapping_key).unwrap(),
+        jwk
+    );
+
+    let wrapping_key = key(P256_JWK_FIXTURE);
+    let token = jwk.encrypt_with_key(Alg::EcdhEs, &wrapping_key).unwrap();
+    assert_eq!(
+        JsonWebKey::decrypt_with_key(&token, &wrapping_key).unwrap(),
+        jwk
+    );
+
+    // The content type must be `jwk+json`.
+    let token = jwe::encrypt(
+        &jwe::Header::new(Alg::A256KW, Enc::A128GCM),
+        jwk.to_string(),
+        &oct(32),
+    )
+    .unwrap();
+    assert!(matches!(
+        JsonWebKey::decrypt_with_key(&token, &oct(32)),
+        Err(jwe::Error::UnexpectedContentType)
+    ));
+}
+
+#[test]
+fn pbes2_params() {
+    let mut header = jwe::Header::new(Alg::Pbes2Hs256A128KW, Enc::A128GCM);
+    header.pbes2_count = Some(1000);
+    let token = jwe::encrypt_with_password(&header, PLAINTEXT, "hunter2").unwrap();
+    assert_eq!(
+        jwe::decrypt_with_password(&token, "hunter2")
+            .unwrap()
+            .plaintext,
+        PLAINTEXT
+    );
+    assert!(matches!(
+        jwe::decrypt(&token, &oct(16)),
+        Err(jwe::Error::Key(Error::MismatchedAlgorithm))
+    ));
+
+    for (salt, count) in [(vec![0; 7], 1000), (vec![0; 8], 0), (vec![0; 8], u32::MAX)] {
+        header.pbes2_salt = Some(salt.into());
+        header.pbes2_count = Some(count);
+        assert!(matches!(
+            jwe::encrypt_with_password(&header, PLAINTEXT, "hunter2"),
+            Err(jwe::Error::InvalidPbes2Params)
+        ));
+    }
+    assert!(matches!(
+        jwe::encrypt_with_password(
+            &jwe::Header::new(Alg::A128KW, Enc::A128GCM),
+            PLAINTEXT,
+            "hunter2"
+        ),
+        Err(jwe::Error::Key(Error::MismatchedAlgorithm))
+    ));
+}
+
+#[test]
+fn header_jwk_is_public() {
+    let jwk = key(ECDH_ES_JWK_FIXTURE);
+    let mut header = jwe::Header::new(Alg::EcdhEs, Enc::A128GCM);
+    header.jwk = Some(Box::new(
+        PrivateJwk::try_from(jwk.clone()).unwrap().to_public(),
+    ));
+    let token = jwe::encrypt(&header, PLAINTEXT, &jwk).unwrap();
+    let decoded = jwe::decode_header(&token).unwrap();
+    assert!(!decoded.jwk.unwrap().key.is_private());
+
+    let private_header = format!(
+        r#"{{"alg":"ECDH-ES","enc":"A128GCM","jwk":{}}}"#,
+        ECDH_ES_JWK_FIXTURE
+    );
+    let token = format!("{}....", crate::utils::base64_encode(private_header));
+    assert!(jwe::decode_header(&token).is_err());
+    let private_epk_header = format!(
+        r#"{{"alg":"ECDH-ES","enc":"A128GCM","epk":{}}}"#,
+        ECDH_ES_JWK_FIXTURE
+    );
+    let token = format!("{}....", crate::utils::base64_encode(private_epk_header));
+    assert!(jwe::decode_header(&token).is_err());
+    assert!(jwe::decrypt(&token, &jwk).is_err());
+}
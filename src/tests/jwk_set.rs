@@ -0,0 +1,139 @@
+use super::*;
+
+fn jwk_set_fixture() -> String {
+    format!(
+        r#"{{
+        "keys": [
+            {},
+            {},
+            {{ "kty": "EC", "crv": "P-256K", "x": "AA", "y": "AA" }},
+            {},
+            {{ "kty": "oct", "k": "AAAA", "alg": "HS256" }}
+        ]
+    }}"#,
+        P256_JWK_FIXTURE, RSA_JWK_FIXTURE, ED25519_JWK_FIXTURE
+    )
+}
+
+fn key_ids<'a>(keys: impl Iterator<Item = &'a JsonWebKey>) -> Vec<Option<&'a str>> {
+    keys.map(|jwk| jwk.key_id.as_deref()).collect()
+}
+
+#[test]
+fn deserialize_jwk_set() {
+    let jwks = JwkSet::from_str(&jwk_set_fixture()).unwrap();
+    assert_eq!(
+        jwks.keys().cloned().collect::<Vec<_>>(),
+        vec![
+            JsonWebKey::from_str(P256_JWK_FIXTURE).unwrap(),
+            JsonWebKey::from_str(RSA_JWK_FIXTURE).unwrap(),
+            JsonWebKey::from_str(ED25519_JWK_FIXTURE).unwrap(),
+        ]
+    );
+    // The unknown curve and the too-short HMAC key are kept, but not parsed.
+    assert_eq!(
+        jwks.unparsed()
+            .map(|value| value["kty"].as_str().unwrap())
+            .collect::<Vec<_>>(),
+        vec!["EC", "oct"]
+    );
+}
+
+#[test]
+fn jwk_set_round_trip() {
+    let json: serde_json::Value = serde_json::from_str(&jwk_set_fixture()).unwrap();
+    let jwks: JwkSet = serde_json::from_value(json.clone()).unwrap();
+    let reserialized: serde_json::Value = serde_json::from_str(&jwks.to_string()).unwrap();
+    assert_eq!(reserialized, json);
+
+    assert!(JwkSet::from_str(r#"{ "kid": "not a set" }"#).is_err());
+    assert_eq!(JwkSet::new().to_string(), r#"{"keys":[]}"#);
+}
+
+#[test]
+fn jwk_set_lookup() {
+    let mut signing_key = JsonWebKey::from_str(ED25519_JWK_FIXTURE).unwrap();
+    signing_key.key_use = Some(KeyUse::Signing);
+    signing_key.key_id = Some("b key".into());
+    let mut jwks: JwkSet = vec![
+        JsonWebKey::from_str(P256_JWK_FIXTURE).unwrap(),
+        JsonWebKey::from_str(RSA_JWK_FIXTURE).unwrap(),
+    ]
+    .into();
+    jwks.push(signing_key.clone());
+
+    assert_eq!(
+        jwks.find_by_key_id("a key"),
+        Some(&JsonWebKey::from_str(P256_JWK_FIXTURE).unwrap())
+    );
+    assert_eq!(jwks.find_by_key_id("b key"), Some(&signing_key));
+    assert_eq!(jwks.find_by_key_id("c key"), None);
+
+    assert_eq!(
+        key_ids(jwks.filter_by_use(KeyUse::Signing)),
+        vec![Some("b key")]
+    );
+    assert_eq!(
+        key_ids(jwks.filter_by_use(KeyUse::Encryption)),
+        vec![Some("a key"), None]
+    );
+    assert_eq!(
+        key_ids(jwks.filter_by_algorithm(Algorithm::ES256)),
+        vec![Some("a key")]
+    );
+    assert_eq!(
+        key_ids(jwks.filter_by_algorithm(Algorithm::PS256)),
+        vec![None]
+    );
+    assert_eq!(
+        key_ids(jwks.filter_by_algorithm(Algorithm::EdDSA)),
+        vec![Some("b key")]
+    );
+    assert_eq!(jwks.filter_by_algorithm(Algorithm::ES384).count(), 0);
+}
+
+#[cfg(feature = "thumbprint")]
+#[test]
+fn jwk_set_find_by_thumbprint() {
+    let jwks = JwkSet::from_str(&jwk_set_fixture()).unwrap();
+    let rsa = JsonWebKey::from_str(RSA_JWK_FIXTURE).unwrap();
+    assert_eq!(jwks.find_by_thumbprint(&rsa.key.thumbprint()), Some(&rsa));
+    assert_eq!(jwks.find_by_thumbprint("AAAA"), None);
+}
+
+#[test]
+fn publish_jwk_set() {
+    let jwks = JwkSet::from_str(&jwk_set_fixture()).unwrap();
+    let published = jwks.to_public();
+    assert_eq!(
+        published.keys().cloned().collect::<Vec<_>>(),
+        vec![
+            PrivateJwk::from_str(P256_JWK_FIXTURE).unwrap().to_public(),
+            PrivateJwk::from_str(RSA_JWK_FIXTURE).unwrap().to_public(),
+            PrivateJwk::from_str(ED25519_JWK_FIXTURE)
+                .unwrap()
+                .to_public(),
+        ]
+    );
+    assert!(published.keys().all(|jwk| !jwk.key.is_private()));
+
+    let json = published.to_string();
+    assert_eq!(
+        serde_json::from_str::<PublicJwkSet>(&json).unwrap(),
+        published
+    );
+    assert_eq!(
+        JwkSet::from_str(&json).unwrap(),
+        JwkSet::from(published.clone())
+    );
+    assert!(serde_json::from_str::<PublicJwkSet>(&jwk_set_fixture()).is_err());
+
+    // Keys pushed without being checked are omitted if they can't be typed.
+    let mut jwks = JwkSet::new();
+    let mut mismatched = JsonWebKey::from_str(P256_JWK_FIXTURE).unwrap();
+    mismatched.algorithm = Some(Algorithm::RS256.into());
+    jwks.push(mismatched.clone());
+    mismatched.key = mismatched.key.to_public().unwrap().into_owned().into();
+    jwks.push(mismatched);
+    assert_eq!(jwks.to_public(), PublicJwkSet::new());
+}
@@ -0,0 +1,193 @@
+use super::*;
+
+fn jwk(json: &str, kid: Option<&str>, key_use: Option<KeyUse>, key_ops: KeyOps) -> JsonWebKey {
+    let mut jwk = JsonWebKey::from_str(json).unwrap();
+    jwk.key_id = kid.map(Into::into);
+    jwk.key_use = key_use;
+    jwk.key_ops = key_ops;
+    jwk
+}
+
+fn key_ids(keys: Vec<&JsonWebKey>) -> Vec<Option<&str>> {
+    keys.into_iter().map(|jwk| jwk.key_id.as_deref()).collect()
+}
+
+#[test]
+fn select_by_key_id() {
+    let jwks: JwkSet = vec![
+        jwk(P256_JWK_FIXTURE, None, None, KeyOps::empty()),
+        jwk(P256_JWK_FIXTURE, Some("a"), None, KeyOps::empty()),
+        jwk(P256_JWK_FIXTURE, Some("b"), None, KeyOps::empty()),
+        jwk(ED25519_JWK_FIXTURE, Some("a"), None, KeyOps::empty()),
+    ]
+    .into();
+    let header = KeyHeader {
+        algorithm: Some(Algorithm::ES256.into()),
+        key_id: Some("a".into()),
+        ..Default::default()
+    };
+    assert_eq!(
+        key_ids(jwks.select(&header, KeyOps::VERIFY)),
+        vec![Some("a"), None]
+    );
+
+    let header = KeyHeader {
+        algorithm: Some(Algorithm::EdDSA.into()),
+        ..Default::default()
+    };
+    assert_eq!(
+        key_ids(jwks.select(&header, KeyOps::VERIFY)),
+        vec![Some("a")]
+    );
+}
+
+#[test]
+fn select_by_use_and_key_ops() {
+    let public_p256 = JsonWebKey::new(
+        JsonWebKey::from_str(P256_JWK_FIXTURE)
+            .unwrap()
+            .key
+            .to_public()
+            .unwrap()
+            .into_owned(),
+    );
+    let jwks: JwkSet = vec![
+        jwk(
+            P256_JWK_FIXTURE,
+            Some("enc"),
+            Some(KeyUse::Encryption),
+            KeyOps::empty(),
+        ),
+        jwk(
+            P256_JWK_FIXTURE,
+            Some("sig"),
+            Some(KeyUse::Signing),
+            KeyOps::empty(),
+        ),
+        jwk(P256_JWK_FIXTURE, Some("sign"), None, KeyOps::SIGN),
+        jwk(P256_JWK_FIXTURE, Some("verify"), None, KeyOps::VERIFY),
+        JsonWebKey {
+            key_id: Some("public".into()),
+            ..public_p256
+        },
+    ]
+    .into();
+    let header = KeyHeader {
+        algorithm: Some(Algorithm::ES256.into()),
+        ..Default::default()
+    };
+    assert_eq!(
+        key_ids(jwks.select(&header, KeyOps::VERIFY)),
+        vec![Some("sig"), Some("verify"), Some("public")]
+    );
+    assert_eq!(
+        key_ids(jwks.select(&header, KeyOps::SIGN)),
+        vec![Some("sig"), Some("sign")]
+    );
+    assert_eq!(
+        key_ids(jwks.select(&header, KeyOps::DERIVE_KEY)),
+        vec![Some("enc")]
+    );
+}
+
+#[test]
+fn select_by_algorithm() {
+    let mut ps256 = jwk(RSA_JWK_FIXTURE, Some("ps256"), None, KeyOps::empty());
+    ps256.algorithm = Some(Algorithm::PS256.into());
+    let jwks: JwkSet = vec![
+        jwk(RSA_JWK_FIXTURE, Some("any"), None, KeyOps::empty()),
+        ps256,
+        jwk(P256_JWK_FIXTURE, Some("es256"), None, KeyOps::empty()),
+    ]
+    .into();
+    let select = |alg: Algorithm| {
+        let header = KeyHeader {
+            algorithm: Some(alg.into()),
+            ..Default::default()
+        };
+        key_ids(jwks.select(&header, KeyOps::VERIFY))
+    };
+    assert_eq!(select(Algorithm::PS256), vec![Some("ps256"), Some("any")]);
+    assert_eq!(select(Algorithm::RS256), vec![Some("any")]);
+    assert_eq!(select(Algorithm::ES256), vec![Some("es256")]);
+    assert_eq!(select(Algorithm::ES384), Vec::<Option<&str>>::new());
+    assert_eq!(
+        key_ids(jwks.select(&KeyHeader::default(), KeyOps::VERIFY)),
+        vec![Some("any"), Some("ps256"), Some("es256")]
+    );
+}
+
+#[test]
+fn select_by_key_material() {
+    let jwks = JwkSet::from_str(
+        r#"{
+        "keys": [
+            { "kty": "EC", "crv": "P-256", "kid": "other", "x5t#S256": "AAAA",
+              "x": "QOMHmv96tVlJv-uNqprnDSKIj5AiLTXKRomXYnav0N0",
+              "y": "TjYZoHnctatEE6NCrKmXQdJJPnNzZEX8nBmZde3AY4k" },
+            { "kty": "EC", "crv": "P-256", "kid": "plain",
+              "x": "QOMHmv96tVlJv-uNqprnDSKIj5AiLTXKRomXYnav0N0",
+              "y": "TjYZoHnctatEE6NCrKmXQdJJPnNzZEX8nBmZde3AY4k" },
+            { "kty": "EC", "crv": "P-256", "kid": "cert", "x5t": "BBBB",
+              "x": "QOMHmv96tVlJv-uNqprnDSKIj5AiLTXKRomXYnav0N0",
+              "y": "TjYZoHnctatEE6NCrKmXQdJJPnNzZEX8nBmZde3AY4k" }
+        ]
+    }"#,
+    )
+    .unwrap();
+
+    let header = KeyHeader {
+        cert_thumbprint: Some("BBBB".into()),
+        ..Default::default()
+    };
+    assert_eq!(
+        key_ids(jwks.select(&header, KeyOps::VERIFY)),
+        vec![Some("cert"), Some("other"), Some("plain")]
+    );
+
+    let header = KeyHeader {
+        cert_thumbprint_sha256: Some("BBBB".into()),
+        ..Default::default()
+    };
+    assert_eq!(
+        key_ids(jwks.select(&header, KeyOps::VERIFY)),
+        vec![Some("plain"), Some("cert")]
+    );
+
+    let header: KeyHeader = serde_json::from_str(&format!(
+        r#"{{ "alg": "ES256", "typ": "JWT", "jwk": {} }}"#,
+        ED25519_JWK_FIXTURE
+    ))
+    .unwrap();
+    assert!(jwks.select(&header, KeyOps::VERIFY).is_empty());
+
+    let header = KeyHeader {
+        jwk: Some(Box::new(jwks.find_by_key_id("other").unwrap().clone())),
+        ..Default::default()
+    };
+    assert_eq!(key_ids(jwks.select(&header, KeyOps::VERIFY)).len(), 3);
+}
+
+#[test]
+fn select_by_jwe_header() {
+    let header: KeyHeader =
+        serde_json::from_str(r#"{"alg":"RSA-OAEP-256","enc":"A256GCM","kid":"k1"}"#).unwrap();
+    assert_eq!(
+        header.algorithm,
+        Some(KeyManagementAlgorithm::RsaOaep256.into())
+    );
+
+    let mut rsa_oaep = jwk(RSA_JWK_FIXTURE, Some("k1"), None, KeyOps::empty());
+    rsa_oaep.algorithm = Some(KeyManagementAlgorithm::RsaOaep.into());
+    let jwks: JwkSet = vec![
+        jwk(P256_JWK_FIXTURE, Some("k1"), None, KeyOps::empty()),
+        rsa_oaep,
+        jwk(RSA_JWK_FIXTURE, Some("k2"), None, KeyOps::empty()),
+        jwk(RSA_JWK_FIXTURE, Some("k1"), None, KeyOps::UNWRAP_KEY),
+    ]
+    .into();
+    assert_eq!(
+        jwks.select(&header, KeyOps::UNWRAP_KEY),
+        vec![jwks.keys().nth(3).unwrap()]
+    );
+}
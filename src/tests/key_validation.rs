@@ -0,0 +1,180 @@
+use super::*;
+
+// From https://tools.ietf.org/html/rfc7748#section-6.1
+static X25519_JWK_FIXTURE: &str = r#"{
+        "kty": "OKP",
+        "crv": "X25519",
+        "d": "dwdtCnMYpX08FsFyUbJmRd9ML4frwJkqsXf7pR25LCo",
+        "x": "hSDwCYkwp1R0i33ctD73Wg2_Og0mOBr066SpjqqbTmo"
+    }"#;
+
+/// Parses the fixture after replacing (or, for `null`, removing) the given parameters.
+fn key_with(fixture: &str, params: &[(&str, serde_json::Value)]) -> Key {
+    let mut jwk: serde_json::Map<String, serde_json::Value> =
+        serde_json::from_str(fixture).unwrap();
+    for (name, value) in params {
+        match value {
+            serde_json::Value::Null => jwk.remove(*name),
+            value => jwk.insert(name.to_string(), value.clone()),
+        };
+    }
+    *serde_json::from_value::<JsonWebKey>(jwk.into())
+        .unwrap()
+        .key
+}
+
+fn key(fixture: &str) -> Key {
+    key_with(fixture, &[])
+}
+
+#[test]
+fn valid_keys() {
+    for fixture in [
+        P256_JWK_FIXTURE,
+        P384_JWK_FIXTURE,
+        P521_JWK_FIXTURE,
+        SECP256K1_JWK_FIXTURE,
+        ED25519_JWK_FIXTURE,
+        X25519_JWK_FIXTURE,
+        RSA_JWK_FIXTURE,
+        RSA_2048_JWK_FIXTURE,
+    ] {
+        let key = key(fixture);
+        assert_eq!(key.validate(), Ok(()));
+        assert_eq!(key.to_public().unwrap().validate(), Ok(()));
+    }
+
+    let key = key_with(
+        RSA_2048_JWK_FIXTURE,
+        &[
+            ("p", serde_json::Value::Null),
+            ("q", serde_json::Value::Null),
+            ("dp", serde_json::Value::Null),
+            ("dq", serde_json::Value::Null),
+            ("qi", serde_json::Value::Null),
+        ],
+    );
+    assert_eq!(key.validate(), Ok(()));
+}
+
+#[test]
+fn invalid_ec_keys() {
+    let zero = "A".repeat(43);
+    let cases = [
+        (
+            vec![("x", "QOMHmv96tVlJv-uNqprnDSKIj5AiLTXKRomXYnav0N4".into())],
+            ValidationError::PointNotOnCurve,
+        ),
+        (
+            vec![("x", zero.clone().into()), ("y", zero.clone().into())],
+            ValidationError::IdentityPoint,
+        ),
+        (
+            vec![("d", zero.into())],
+            ValidationError::PrivateKeyOutOfRange,
+        ),
+        (
+            vec![("d", format!("{}8", "_".repeat(42)).into())],
+            ValidationError::PrivateKeyOutOfRange,
+        ),
+        (
+            vec![("d", "ZoKQ9j4dhIBlMRVrv-QG8P_T9sutv3_95eio9MtpgKc".into())],
+            ValidationError::MismatchedPrivateKey,
+        ),
+    ];
+    for (params, err) in cases {
+        assert_eq!(key_with(P256_JWK_FIXTURE, &params).validate(), Err(err));
+    }
+
+    let key = Key::EC {
+        curve: Curve::P384,
+        d: None,
+        x: vec![1; 32].into(),
+        y: vec![1; 32].into(),
+    };
+    assert_eq!(key.validate(), Err(ValidationError::InvalidLength));
+}
+
+#[test]
+fn invalid_okp_keys() {
+    // The encoding of the Ed25519 identity point.
+    let identity =
+        base64::encode_config([[1].as_slice(), &[0; 31]].concat(), base64::URL_SAFE_NO_PAD);
+    assert_eq!(
+        key_with(ED25519_JWK_FIXTURE, &[("x", identity.into())]).validate(),
+        Err(ValidationError::IdentityPoint)
+    );
+    assert_eq!(
+        key_with(
+            ED25519_JWK_FIXTURE,
+            &[("d", "nWGxne_9WmC6hEr0kuwsxERJxWl7MmkZcDusAxyuf2E".into())]
+        )
+        .validate(),
+        Err(ValidationError::MismatchedPrivateKey)
+    );
+
+    assert_eq!(
+        key_with(X25519_JWK_FIXTURE, &[("x", "A".repeat(43).into())]).validate(),
+        Err(ValidationError::IdentityPoint)
+    );
+    assert_eq!(
+        key_with(
+            X25519_JWK_FIXTURE,
+            &[("d", "dwdtCnMYpX08FsFyUbJmRd9ML4frwJkqsXf7pR25LCs".into())]
+        )
+        .validate(),
+        Err(ValidationError::MismatchedPrivateKey)
+    );
+
+    let key = Key::OKP {
+        curve: OkpCurve::Ed25519,
+        d: None,
+        x: vec![1; 31].into(),
+    };
+    assert_eq!(key.validate(), Err(ValidationError::InvalidLength));
+}
+
+#[test]
+fn invalid_rsa_keys() {
+    let p = serde_json::from_str::<serde_json::Value>(RSA_JWK_FIXTURE).unwrap()["p"].clone();
+    let cases = [
+        (
+            vec![("n", "pCzbcd9kjvg5rfGHdEMWnXo49zbB6FLQ-m0B0BvVp0aojVWYa0xujC-ZP7ZhxByPxyc2PazwFJJi9ivZ_ggRwg".into())],
+            ValidationError::InvalidRsaModulus,
+        ),
+        (vec![("e", "AQAA".into())], ValidationError::InvalidRsaExponent),
+        (vec![("e", "AQ".into())], ValidationError::InvalidRsaExponent),
+        (
+            vec![("d", "Qdp8a8Df5TlMaaloXApNF_3eu8sLHNWbXdg70e5YVTAs0WUfaIf5c3n96RrDDAzmMEwgKnJ7A1NJ9Nlzz4Z0AA".into())],
+            ValidationError::MismatchedPrivateKey,
+        ),
+        (
+            vec![("dq", serde_json::Value::Null)],
+            ValidationError::IncompleteRsaCrtParams,
+        ),
+        (vec![("q", p)], ValidationError::RsaModulusMismatch),
+        (
+            vec![("dp", "qVnLiKeoSG_Olz17OGBGd4a2sqVFnrjh_51wuaQDdTg".into())],
+            ValidationError::InconsistentRsaCrtParam("dp"),
+        ),
+        (
+            vec![("dq", "GL_Ec6xYg2z1FRfyyGyU1lgf0BJFTZcfNI8ISIN5ssA".into())],
+            ValidationError::InconsistentRsaCrtParam("dq"),
+        ),
+        (
+            vec![("qi", "adhQHH8IGXFfLEMnZ5t_TeCp5zgSwQktJ2lmylxUG0I".into())],
+            ValidationError::InconsistentRsaCrtParam("qi"),
+        ),
+    ];
+    for (params, err) in cases {
+        assert_eq!(key_with(RSA_JWK_FIXTURE, &params).validate(), Err(err));
+    }
+}
+
+#[test]
+fn invalid_symmetric_key() {
+    let key = Key::Symmetric {
+        key: Vec::new().into(),
+    };
+    assert_eq!(key.validate(), Err(ValidationError::EmptySymmetricKey));
+}
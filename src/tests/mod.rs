@@ -1,3 +1,13 @@
+#[cfg(feature = "jwe")]
+mod jwe;
+mod jwk_set;
+#[cfg(feature = "jws")]
+mod jws;
+#[cfg(feature = "jwt")]
+mod jwt;
+mod key_selection;
+#[cfg(feature = "validate")]
+mod key_validation;
 #[cfg(feature = "pkcs-convert")]
 mod pkcs_convert;
 #[cfg(feature = "thumbprint")]
@@ -7,8 +17,6 @@ use super::*;
 
 use std::str::FromStr;
 
-use crate::byte_array::ByteArray;
-
 // Generated using https://mkjwk.org
 static P256_JWK_FIXTURE: &str = r#"{
         "kty": "EC",
@@ -35,6 +43,79 @@ static RSA_JWK_FIXTURE: &str = r#"{
         "n": "pCzbcd9kjvg5rfGHdEMWnXo49zbB6FLQ-m0B0BvVp0aojVWYa0xujC-ZP7ZhxByPxyc2PazwFJJi9ivZ_ggRww"
     }"#;
 
+// Generated using Python's `cryptography` package.
+static P384_JWK_FIXTURE: &str = r#"{
+        "kty": "EC",
+        "crv": "P-384",
+        "d": "S_qjef6tTSAMUC7KyXbvTpxkb9lPCxwMdYq9qzi_j1c1ZgSdL4jtIOJyhZzjUkjs",
+        "x": "0jULqxe-mxiFOv6pkjZR8kDVpFbSYlYl_rkogwrT0av7C6L6pLja3Q9HnYaM5TJA",
+        "y": "Pnwzs6YSirRHDzXlbCr78OjzC3zb5ev5iWUDkEfb_NgaHZy8G1sp76-hL8O_wdrg",
+        "alg": "ES384"
+    }"#;
+
+// Generated using Python's `cryptography` package.
+static P521_JWK_FIXTURE: &str = r#"{
+        "kty": "EC",
+        "crv": "P-521",
+        "d": "AYlm8cUFF9fkZWtY2tAsm0M7mQyqAYI7VIemBoxAUJYJgSTPXg_UI9gS8R33rUAq4lMZ_pQgAN1euPOObhN3muvH",
+        "x": "ANEvaHvrPnRBqFMqhxx-jY57UEZhKwmwONQhrdKyrWx7TWyFtETsiz4_9oEkXLYqiHTt92NbiYFjWUs-LHb4qUkd",
+        "y": "AIhWWJ0rpCN0KEI3d2b82rh4Il9hgVduqFs4eFD5qTs0NjbQKiOJVwQkvppkfHkdpSfDCfkJYqAXfCr71LZmuge0",
+        "alg": "ES512"
+    }"#;
+
+// Generated using Python's `cryptography` package.
+static SECP256K1_JWK_FIXTURE: &str = r#"{
+        "kty": "EC",
+        "crv": "secp256k1",
+        "d": "sx47g0F8BIOTdU1TNFccDVP3_o-nCPQjjAX1Hfr_vVo",
+        "x": "HVN9mDqpkkAbp1dlwC0D9v2OOsPHJC7Xj0vi8sVGBbA",
+        "y": "mGyC3bg_LR2D9KWdTUzLZriiQUqGOqC6i-6PlZYBtNw",
+        "alg": "ES256K"
+    }"#;
+
+// From https://tools.ietf.org/html/rfc8037#appendix-A.1
+static ED25519_JWK_FIXTURE: &str = r#"{
+        "kty": "OKP",
+        "crv": "Ed25519",
+        "d": "nWGxne_9WmC6hEr0kuwsxERJxWl7MmkZcDusAxyuf2A",
+        "x": "11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"
+    }"#;
+
+// Generated using Python's `cryptography` package.
+#[cfg(feature = "pkcs-convert")]
+static X448_JWK_FIXTURE: &str = r#"{
+        "kty": "OKP",
+        "crv": "X448",
+        "d": "wBuvyPjPA93Dprn1XMZo0zD_gM9wwm34GzuhxPAvi2Eujm3tsoRKJeRUuOhDY1dR3IwDNhsP2ME",
+        "x": "K6YsiwJdOLx4D0WLec1dmLe9mmJhPgxkdSfXosIIzJOmLfwIamPxAv55xfypHx8qCFGNNz_x6a4"
+    }"#;
+
+// Generated using Python's `cryptography` package.
+#[cfg(any(
+    feature = "jwe",
+    feature = "jws",
+    feature = "pkcs-convert",
+    feature = "validate"
+))]
+static RSA_2048_JWK_FIXTURE: &str = r#"{
+        "kty": "RSA",
+        "n": "0qBsvRlWiv-sYKxqh_lnyIN0TmUG_e_JSR7e8xHQ_GFRCVVBeHcrkuKtVpZFERviWK-gwu_lrm7oz_wJE1e8rSHvJfXLhzS_D4uyuASJSqRRQFbPPC-_IvG0z3NabXhP6KWBsaK456lgzQ5PJnI5nuxrSeM9JRTRK4zoiCssta6OJ85mXHFcenMKWKvRNjTEcGMTOEi9hw6ynHyJ-rhRIQv4YZPonY26byO8ZOIqUSFc6x0n8I18wpzOOQHfQ-Y-U7RczfOTlU7mr_ZUfLykhGlYjfq4RtLPbkETV4pOTXgpJS6hCMjIz2OdqHz6uByPfyXs0WoICQIwTBj1JdCVSw",
+        "e": "AQAB",
+        "d": "Ad4zBucxLhKf9qFNRJEA59keKUbv3344XKrfiGwcI5f5zFe5jiRIbBdmVXsH6gAm-WlEcg8HRTfDQezzS6eew_j7LsI5Z8E7qsX2EFuB8RaGbCuKsy_WGx6s-7mRZS7sToBzCskKGovcV84ayydFvRr4U7qmTg_-3gPmA5jrwnoMQ0viSiAbcp3GzmlOWhSsvKgiZlmq0hKfZ-FHU6iYP3IA_Py9U8Jmm0bcg2l-dUvi_s-LxW81m0Uek6Y3dnYJeJc66MJHpcPoY0KuLxCzSGEdBFdOc8lpZ6Wk3efNtadAdOvNLVa3DHN8OCPbslB-TZpaXLy_kqnrEPUkq2VySQ",
+        "p": "9oRh79baHodeeKAdYg5CW4QBCnyQfitgnRajC1vJvnr6et4yFsnviEHKFN7C32y4IrQotLC30cS1JNZBsFRj1KuCt8-m8KTzJ36mrxF4HSMUopjz2bOmb5rncEgWPYS0S6w06-vm0LUpkAhq2dpy0b92q7Co92l_ZDH6GWC7p-U",
+        "q": "2rqa5IhaNREHtTeA7vYQ-qogk-Xsrw24fjoJBW0yDVI5Ca2v-8rUsyBrgv4qmwx8rKtjFvWBOuf0Qe7CI_fnD6zgeqp7PkBfsUTOyi-2AxAdChQ0klX2KdHozUwi2XpIr6NwStp4lGOxnA5YYP9x-Owsv9cZ5kGTjjMu1HwpFW8",
+        "dp": "0URcJ0_KZfQgfGHfz6evKSUMBSztNE36fCAFVNUck_itvbP17WaLUq4WsFTSNPyD6-HoVEtYIFjONXE03k0lWhomo4dBfjNovvRC54b64jEviUAycXKSLVfgdG6dgqeurjaHzma50B6aI80hd8J7ihbw1JrpNO532EScvQJjCwk",
+        "dq": "2BsMlIT_k3S7B9nJ4s9toSvbuiSuf5pv0Q8oAJuNm0nEnwJjYMRa-IwsjSTwFXb162f-JB32S6wHUelDGWSCf6NPuwKbd7sEkNGp-mu_SjFub5ndA0Q5TcJeYBA8UmEWVaqGdP57qikBv20Q9u0Lv2QhJn2w0iWyUwnQ2BHASbM",
+        "qi": "Y9sMnCJpFarT6EaXLrbh9ZztsYPmVrkRe2Ve4a71mLIQkE8N12dwFylXxT0JuprN15ebCdz13vWQyCRc2eg2VtzkwSg8VK_-2cBeGj-na6e9mjUMFDuxbuj3sj__3wvNG-oehWs70yEg1wfEYswiA5YRqM3E24mma_0siUDlt3k"
+    }"#;
+
+// From https://tools.ietf.org/html/rfc7515#appendix-A.1
+#[cfg(feature = "jws")]
+static HMAC_JWK_FIXTURE: &str = r#"{
+        "kty": "oct",
+        "k": "AyM1SysPpbyDfgZld3umj1qzKObwVMkoqQ-EstJQLr_T-1qS0gZH75aKtMN3Yj0iPS4hcgUuTwjAzZr1Z9CAow"
+    }"#;
+
 #[cfg(any(feature = "pkcs-convert", feature = "thumbprint"))]
 static OCT_JWK_FIXTURE: &str = r#"{
         "kty": "oct",
@@ -50,24 +131,31 @@ fn deserialize_es256() {
             key: Box::new(Key::EC {
                 // The parameters were decoded using a 10-liner Rust script.
                 curve: Curve::P256,
-                d: Some(ByteArray::from_slice(&[
-                    102, 130, 144, 246, 62, 29, 132, 128, 101, 49, 21, 107, 191, 228, 6, 240, 255,
-                    211, 246, 203, 173, 191, 127, 253, 229, 232, 168, 244, 203, 105, 128, 168
-                ])),
-                x: ByteArray::from_slice(&[
+                d: Some(
+                    vec![
+                        102, 130, 144, 246, 62, 29, 132, 128, 101, 49, 21, 107, 191, 228, 6, 240,
+                        255, 211, 246, 203, 173, 191, 127, 253, 229, 232, 168, 244, 203, 105, 128,
+                        168
+                    ]
+                    .into()
+                ),
+                x: vec![
                     64, 227, 7, 154, 255, 122, 181, 89, 73, 191, 235, 141, 170, 154, 231, 13, 34,
                     136, 143, 144, 34, 45, 53, 202, 70, 137, 151, 98, 118, 175, 208, 221
-                ]),
-                y: ByteArray::from_slice(&[
+                ]
+                .into(),
+                y: vec![
                     78, 54, 25, 160, 121, 220, 181, 171, 68, 19, 163, 66, 172, 169, 151, 65, 210,
                     73, 62, 115, 115, 100, 69, 252, 156, 25, 153, 117, 237, 192, 99, 137
-                ])
+                ]
+                .into()
             }),
-            algorithm: Some(Algorithm::ES256),
+            algorithm: Some(Algorithm::ES256.into()),
             key_id: Some("a key".into()),
             key_ops: KeyOps::empty(),
             key_use: Some(KeyUse::Encryption),
             x5: Default::default(),
+            extensions: Default::default(),
         }
     );
 }
@@ -78,14 +166,15 @@ fn serialize_es256() {
         key: Box::new(Key::EC {
             curve: Curve::P256,
             d: None,
-            x: ByteArray::from_slice(&[1u8; 32]),
-            y: ByteArray::from_slice(&[2u8; 32]),
+            x: vec![1u8; 32].into(),
+            y: vec![2u8; 32].into(),
         }),
         key_id: None,
         algorithm: None,
         key_ops: KeyOps::empty(),
         key_use: None,
         x5: Default::default(),
+        extensions: Default::default(),
     };
     assert_eq!(
         jwk.to_string(),
@@ -93,6 +182,54 @@ fn serialize_es256() {
     );
 }
 
+#[test]
+fn deserialize_es384_es512_es256k() {
+    for (fixture, curve, alg) in [
+        (P384_JWK_FIXTURE, Curve::P384, Algorithm::ES384),
+        (P521_JWK_FIXTURE, Curve::P521, Algorithm::ES512),
+        (SECP256K1_JWK_FIXTURE, Curve::Secp256k1, Algorithm::ES256K),
+    ] {
+        let jwk = JsonWebKey::from_str(fixture).unwrap();
+        assert_eq!(jwk.algorithm, Some(alg.into()));
+        match *jwk.key {
+            Key::EC {
+                curve: crv,
+                d: Some(d),
+                x,
+                y,
+            } if crv == curve => {
+                assert_eq!(d.len(), curve.coordinate_len());
+                assert_eq!(x.len(), curve.coordinate_len());
+                assert_eq!(y.len(), curve.coordinate_len());
+            }
+            k => panic!("unexpected key: {:?}", k),
+        }
+    }
+}
+
+#[test]
+fn ec_mismatched_coordinate_len() {
+    // A P-256 key claiming to be on P-384.
+    let jwk_str = r#"{
+        "kty": "EC",
+        "crv": "P-384",
+        "x": "QOMHmv96tVlJv-uNqprnDSKIj5AiLTXKRomXYnav0N0",
+        "y": "TjYZoHnctatEE6NCrKmXQdJJPnNzZEX8nBmZde3AY4k"
+    }"#;
+    assert!(matches!(
+        JsonWebKey::from_str(jwk_str),
+        Err(Error::Serde(_))
+    ));
+
+    let mut k: serde_json::Map<String, serde_json::Value> =
+        serde_json::from_str(P384_JWK_FIXTURE).unwrap();
+    k.insert(
+        "d".into(),
+        "ZoKQ9j4dhIBlMRVrv-QG8P_T9sutv3_95eio9MtpgKg".into(),
+    );
+    assert!(JsonWebKey::from_str(&serde_json::to_string(&k).unwrap()).is_err());
+}
+
 #[cfg(all(feature = "jwt-convert", feature = "generate"))]
 #[test]
 fn generate_p256() {
@@ -106,26 +243,250 @@ fn generate_p256() {
     let mut the_jwk = JsonWebKey::new(Key::generate_p256());
     the_jwk.set_algorithm(Algorithm::ES256).unwrap();
 
+    let alg: jwt::Algorithm = the_jwk.algorithm.unwrap().try_into().unwrap();
     let encoding_key = jwt::EncodingKey::from_ec_der(&the_jwk.key.to_der());
     let token = jwt::encode(
-        &jwt::Header::new(the_jwk.algorithm.unwrap().into()),
+        &jwt::Header::new(alg),
         &TokenClaims { exp: 0 },
         &encoding_key,
     )
     .unwrap();
 
-    let mut validation = jwt::Validation::new(the_jwk.algorithm.unwrap().into());
+    let mut validation = jwt::Validation::new(alg);
     validation.validate_exp = false;
     let public_pem = the_jwk.key.to_public().unwrap().to_pem();
     let decoding_key = jwt::DecodingKey::from_ec_pem(public_pem.as_bytes()).unwrap();
     jwt::decode::<TokenClaims>(&token, &decoding_key, &validation).unwrap();
 }
 
+#[cfg(feature = "jwt-convert")]
+#[test]
+fn jwt_convert_p384() {
+    extern crate jsonwebtoken as jwt;
+
+    #[derive(Serialize, Deserialize)]
+    struct TokenClaims {
+        exp: usize,
+    }
+
+    let the_jwk = JsonWebKey::from_str(P384_JWK_FIXTURE).unwrap();
+    let alg: jwt::Algorithm = the_jwk.algorithm.unwrap().try_into().unwrap();
+    let token = jwt::encode(
+        &jwt::Header::new(alg),
+        &TokenClaims { exp: 0 },
+        &the_jwk.key.to_encoding_key(),
+    )
+    .unwrap();
+
+    let mut validation = jwt::Validation::new(alg);
+    validation.validate_exp = false;
+    jwt::decode::<TokenClaims>(&token, &the_jwk.key.to_decoding_key(), &validation).unwrap();
+
+    let es512: Result<jwt::Algorithm, _> = Algorithm::ES512.try_into();
+    assert!(matches!(
+        es512,
+        Err(ConversionError::UnsupportedAlgorithm(Algorithm::ES512))
+    ));
+}
+
+#[cfg(feature = "generate")]
+#[test]
+fn generate_secp256k1() {
+    let mut the_jwk = JsonWebKey::new(Key::generate_secp256k1());
+    the_jwk.set_algorithm(Algorithm::ES256K).unwrap();
+    assert!(the_jwk.key.is_private());
+    let round_tripped = JsonWebKey::from_str(&the_jwk.to_string()).unwrap();
+    assert_eq!(round_tripped, the_jwk);
+}
+
+#[cfg(feature = "generate")]
+#[test]
+fn from_private_key() {
+    for fixture in [
+        P256_JWK_FIXTURE,
+        P384_JWK_FIXTURE,
+        P521_JWK_FIXTURE,
+        SECP256K1_JWK_FIXTURE,
+        ED25519_JWK_FIXTURE,
+    ] {
+        let key = *JsonWebKey::from_str(fixture).unwrap().key;
+        let derived = match &key {
+            Key::EC {
+                curve, d: Some(d), ..
+            } => Key::from_ec_private_key(*curve, d).unwrap(),
+            Key::OKP {
+                curve, d: Some(d), ..
+            } => Key::from_okp_private_key(*curve, d).unwrap(),
+            _ => unreachable!(),
+        };
+        assert_eq!(derived, key);
+    }
+
+    let key = Key::generate_x25519();
+    if let Key::OKP { d: Some(d), .. } = &key {
+        assert_eq!(Key::from_okp_private_key(OkpCurve::X25519, d).unwrap(), key);
+    }
+
+    assert!(matches!(
+        Key::from_ec_private_key(Curve::P256, [0; 32]),
+        Err(Error::InvalidPrivateKey)
+    ));
+    assert!(matches!(
+        Key::from_ec_private_key(Curve::P256, [0xff; 32]),
+        Err(Error::InvalidPrivateKey)
+    ));
+    assert!(matches!(
+        Key::from_okp_private_key(OkpCurve::Ed25519, [1; 31]),
+        Err(Error::InvalidPrivateKey)
+    ));
+    assert!(matches!(
+        Key::from_okp_private_key(OkpCurve::X448, [1; 56]),
+        Err(Error::UnsupportedCurve("X448"))
+    ));
+}
+
+#[test]
+fn deserialize_eddsa() {
+    let mut jwk = JsonWebKey::from_str(ED25519_JWK_FIXTURE).unwrap();
+    match &*jwk.key {
+        Key::OKP {
+            curve: OkpCurve::Ed25519,
+            d: Some(d),
+            x,
+        } => {
+            assert_eq!(d.len(), 32);
+            assert_eq!(x.len(), 32);
+        }
+        k => panic!("unexpected key: {:?}", k),
+    }
+    jwk.set_algorithm(Algorithm::EdDSA).unwrap();
+    assert!(jwk.key.is_private());
+    assert!(!jwk.key.to_public().unwrap().is_private());
+    assert_eq!(
+        serde_json::to_string(&jwk.key.to_public().unwrap()).unwrap(),
+        r#"{"kty":"OKP","crv":"Ed25519","x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"}"#
+    );
+}
+
+#[test]
+fn okp_mismatched_key_len() {
+    let jwk_str = r#"{
+        "kty": "OKP",
+        "crv": "Ed448",
+        "x": "11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"
+    }"#;
+    assert!(matches!(
+        JsonWebKey::from_str(jwk_str),
+        Err(Error::Serde(_))
+    ));
+}
+
+#[cfg(all(feature = "jwt-convert", feature = "generate"))]
+#[test]
+fn generate_ed25519() {
+    extern crate jsonwebtoken as jwt;
+
+    #[derive(Serialize, Deserialize)]
+    struct TokenClaims {
+        exp: usize,
+    }
+
+    let mut the_jwk = JsonWebKey::new(Key::generate_ed25519());
+    the_jwk.set_algorithm(Algorithm::EdDSA).unwrap();
+
+    let alg: jwt::Algorithm = the_jwk.algorithm.unwrap().try_into().unwrap();
+    let token = jwt::encode(
+        &jwt::Header::new(alg),
+        &TokenClaims { exp: 0 },
+        &the_jwk.key.to_encoding_key(),
+    )
+    .unwrap();
+
+    let mut validation = jwt::Validation::new(alg);
+    validation.validate_exp = false;
+    let decoding_key = the_jwk.key.to_public().unwrap().to_decoding_key();
+    jwt::decode::<TokenClaims>(&token, &decoding_key, &validation).unwrap();
+}
+
+#[cfg(feature = "generate")]
+#[test]
+fn generate_x25519() {
+    let mut the_jwk = JsonWebKey::new(Key::generate_x25519());
+    assert!(the_jwk.set_algorithm(Algorithm::EdDSA).is_err());
+    assert!(the_jwk.key.is_private());
+    let round_tripped = JsonWebKey::from_str(&the_jwk.to_string()).unwrap();
+    assert_eq!(round_tripped, the_jwk);
+}
+
+#[cfg(feature = "generate")]
+#[test]
+fn generate_with_rng() {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    // RSA is omitted because generating keys is slow without optimizations.
+    let generators: [fn(&mut StdRng) -> Key; 5] = [
+        |rng| Key::generate_symmetric_with_rng(256, rng),
+        Key::generate_p256_with_rng,
+        Key::generate_secp256k1_with_rng,
+        Key::generate_ed25519_with_rng,
+        Key::generate_x25519_with_rng,
+    ];
+    for generate in generators {
+        let key = generate(&mut StdRng::seed_from_u64(0));
+        assert_eq!(generate(&mut StdRng::seed_from_u64(0)), key);
+        assert_ne!(generate(&mut StdRng::seed_from_u64(1)), key);
+    }
+}
+
+#[cfg(all(feature = "jwt-convert", feature = "generate"))]
+#[test]
+fn generate_rsa() {
+    extern crate jsonwebtoken as jwt;
+
+    #[derive(Serialize, Deserialize)]
+    struct TokenClaims {
+        exp: usize,
+    }
+
+    assert!(matches!(
+        Key::generate_rsa(1024),
+        Err(Error::UnsupportedKeySize(1024))
+    ));
+
+    let mut the_jwk = JsonWebKey::new(Key::generate_rsa(2048).unwrap());
+    the_jwk.set_algorithm(Algorithm::RS256).unwrap();
+    match &*the_jwk.key {
+        Key::RSA {
+            public,
+            private: Some(private),
+        } => {
+            assert_eq!(public.n.len(), 256);
+            assert!(!public.has_weak_exponent());
+            assert!(private.p.is_some() && private.q.is_some());
+            assert!(private.dp.is_some() && private.dq.is_some() && private.qi.is_some());
+        }
+        _ => panic!("expected an RSA private key"),
+    }
+
+    let alg: jwt::Algorithm = the_jwk.algorithm.unwrap().try_into().unwrap();
+    let token = jwt::encode(
+        &jwt::Header::new(alg),
+        &TokenClaims { exp: 0 },
+        &the_jwk.key.to_encoding_key(),
+    )
+    .unwrap();
+
+    let mut validation = jwt::Validation::new(alg);
+    validation.validate_exp = false;
+    let decoding_key = the_jwk.key.to_public().unwrap().to_decoding_key();
+    jwt::decode::<TokenClaims>(&token, &decoding_key, &validation).unwrap();
+}
+
 #[test]
 fn deserialize_hs256() {
     let jwk_str = r#"{
         "kty": "oct",
-        "k": "tAON6Q",
+        "k": "tLW2t7i5uru8vb6_wMHCw8TFxsfIycrLzM3Oz9DR0tM",
         "alg": "HS256",
         "key_ops": ["verify", "sign"]
     }"#;
@@ -134,18 +495,44 @@ fn deserialize_hs256() {
         jwk,
         JsonWebKey {
             key: Box::new(Key::Symmetric {
-                // The parameters were decoded using a 10-liner Rust script.
-                key: vec![180, 3, 141, 233].into(),
+                key: (180..212).collect::<Vec<u8>>().into(),
             }),
-            algorithm: Some(Algorithm::HS256),
+            algorithm: Some(Algorithm::HS256.into()),
             key_id: None,
             key_ops: KeyOps::SIGN | KeyOps::VERIFY,
             key_use: None,
             x5: Default::default(),
+            extensions: Default::default(),
         }
     );
 }
 
+#[test]
+fn hmac_key_len() {
+    let mut jwk = JsonWebKey::new(Key::Symmetric {
+        key: vec![42; 48].into(),
+    });
+    jwk.set_algorithm(Algorithm::HS256).unwrap();
+    jwk.set_algorithm(Algorithm::HS384).unwrap();
+    match jwk.set_algorithm(Algorithm::HS512) {
+        Err(Error::InsufficientKeyLength {
+            min_len: 64,
+            len: 48,
+        }) => {}
+        v => panic!("expected InsufficientKeyLength, got {:?}", v),
+    }
+    assert_eq!(jwk.algorithm, Some(Algorithm::HS384.into()));
+
+    let jwk_str = r#"{ "kty": "oct", "k": "tAON6Q", "alg": "HS256" }"#;
+    assert!(matches!(
+        JsonWebKey::from_str(jwk_str),
+        Err(Error::InsufficientKeyLength {
+            min_len: 32,
+            len: 4
+        })
+    ));
+}
+
 #[test]
 fn serialize_hs256() {
     let jwk = JsonWebKey {
@@ -157,6 +544,7 @@ fn serialize_hs256() {
         key_ops: KeyOps::empty(),
         key_use: None,
         x5: Default::default(),
+        extensions: Default::default(),
     };
     assert_eq!(
         jwk.to_string(),
@@ -172,7 +560,7 @@ fn deserialize_rs256() {
         JsonWebKey {
             key: Box::new(Key::RSA {
                 public: RsaPublic {
-                    e: PublicExponent,
+                    e: vec![1, 0, 1].into(),
                     n: vec![
                         164, 44, 219, 113, 223, 100, 142, 248, 57, 173, 241, 135, 116, 67, 22, 157,
                         122, 56, 247, 54, 193, 232, 82, 208, 250, 109, 1, 208, 27, 213, 167, 70,
@@ -234,16 +622,99 @@ fn deserialize_rs256() {
             key_ops: KeyOps::WRAP_KEY,
             key_use: Some(KeyUse::Encryption),
             x5: Default::default(),
+            extensions: Default::default(),
         }
     );
 }
 
+#[test]
+fn rsa_algorithms() {
+    let mut jwk = JsonWebKey::from_str(RSA_JWK_FIXTURE).unwrap();
+    for alg in [
+        Algorithm::RS256,
+        Algorithm::RS384,
+        Algorithm::RS512,
+        Algorithm::PS256,
+        Algorithm::PS384,
+        Algorithm::PS512,
+    ] {
+        jwk.set_algorithm(alg).unwrap();
+        let round_tripped = JsonWebKey::from_str(&jwk.to_string()).unwrap();
+        assert_eq!(round_tripped.algorithm, Some(alg.into()));
+    }
+    let jwk_str = jwk.to_string();
+    assert!(jwk_str.contains(r#""alg":"PS512""#), "{}", jwk_str);
+}
+
+#[cfg(feature = "jwt-convert")]
+#[test]
+fn rsa_algorithms_to_jwt() {
+    extern crate jsonwebtoken as jwt;
+
+    for (alg, jwt_alg) in [
+        (Algorithm::RS256, jwt::Algorithm::RS256),
+        (Algorithm::RS384, jwt::Algorithm::RS384),
+        (Algorithm::RS512, jwt::Algorithm::RS512),
+        (Algorithm::PS256, jwt::Algorithm::PS256),
+        (Algorithm::PS384, jwt::Algorithm::PS384),
+        (Algorithm::PS512, jwt::Algorithm::PS512),
+    ] {
+        assert_eq!(jwt::Algorithm::try_from(alg).unwrap(), jwt_alg);
+    }
+}
+
+#[test]
+fn rsa_public_exponent() {
+    let mut k: serde_json::Map<String, serde_json::Value> =
+        serde_json::from_str(RSA_JWK_FIXTURE).unwrap();
+    k.insert("e".into(), "Aw".into());
+    let jwk = JsonWebKey::from_str(&serde_json::to_string(&k).unwrap()).unwrap();
+    match &*jwk.key {
+        Key::RSA { public, .. } => {
+            assert_eq!(&*public.e, &[3]);
+            assert!(public.has_weak_exponent());
+        }
+        k => panic!("unexpected key: {:?}", k),
+    }
+    assert!(jwk.to_string().contains(r#""e":"Aw""#));
+
+    // The legacy padded encoding is 65537, not the big-endian 65792.
+    k.insert("e".into(), "AQABAA==".into());
+    let jwk = JsonWebKey::from_str(&serde_json::to_string(&k).unwrap()).unwrap();
+    match &*jwk.key {
+        Key::RSA { public, .. } => assert_eq!(&*public.e, &[1, 0, 1]),
+        k => panic!("unexpected key: {:?}", k),
+    }
+    assert!(jwk.to_string().contains(r#""e":"AQAB""#));
+    assert_eq!(jwk, JsonWebKey::from_str(RSA_JWK_FIXTURE).unwrap());
+
+    let jwk = JsonWebKey::from_str(RSA_JWK_FIXTURE).unwrap();
+    match &*jwk.key {
+        Key::RSA { public, .. } => assert!(!public.has_weak_exponent()),
+        k => panic!("unexpected key: {:?}", k),
+    }
+
+    for (e, is_weak) in [
+        (vec![], true),
+        (vec![0, 1, 0, 1], false),
+        (vec![1, 0, 0], true),
+        (vec![0, 0, 255], true),
+        (vec![1, 0, 0, 0, 0, 0, 0, 0, 1], false),
+    ] {
+        let public = RsaPublic {
+            e: e.into(),
+            n: vec![1].into(),
+        };
+        assert_eq!(public.has_weak_exponent(), is_weak, "{:?}", public.e);
+    }
+}
+
 #[test]
 fn serialize_rs256() {
     let jwk = JsonWebKey {
         key: Box::new(Key::RSA {
             public: RsaPublic {
-                e: PublicExponent,
+                e: vec![1, 0, 1].into(),
                 n: vec![105, 183, 62].into(),
             },
             private: Some(RsaPrivate {
@@ -260,6 +731,7 @@ fn serialize_rs256() {
         key_ops: KeyOps::empty(),
         key_use: None,
         x5: Default::default(),
+        extensions: Default::default(),
     };
     assert_eq!(
         jwk.to_string(),
@@ -280,6 +752,7 @@ fn mismatched_algorithm() {
 
     assert_mismatched_alg!(r#"{ "kty": "oct", "k": "tAON6Q", "alg": "ES256" }"#);
     assert_mismatched_alg!(r#"{ "kty": "oct", "k": "tAON6Q", "alg": "RS256" }"#);
+    assert_mismatched_alg!(r#"{ "kty": "oct", "k": "tAON6Q", "alg": "PS256" }"#);
 
     assert_mismatched_alg!(
         r#"{
@@ -301,6 +774,32 @@ fn mismatched_algorithm() {
             "alg": "HS256"
         }"#
     );
+    assert_mismatched_alg!(
+        r#"{
+            "kty": "EC",
+            "crv": "P-256",
+            "x": "QOMHmv96tVlJv-uNqprnDSKIj5AiLTXKRomXYnav0N0",
+            "y": "TjYZoHnctatEE6NCrKmXQdJJPnNzZEX8nBmZde3AY4k",
+            "alg": "ES384"
+        }"#
+    );
+    assert_mismatched_alg!(
+        r#"{
+            "kty": "EC",
+            "crv": "P-256",
+            "x": "QOMHmv96tVlJv-uNqprnDSKIj5AiLTXKRomXYnav0N0",
+            "y": "TjYZoHnctatEE6NCrKmXQdJJPnNzZEX8nBmZde3AY4k",
+            "alg": "ES256K"
+        }"#
+    );
+    assert_mismatched_alg!(
+        r#"{
+            "kty": "OKP",
+            "crv": "X25519",
+            "x": "11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo",
+            "alg": "EdDSA"
+        }"#
+    );
 }
 
 #[cfg(feature = "generate")]
@@ -343,6 +842,273 @@ fn rsa_is_private() {
     assert!(!public_jwk.key.to_public().unwrap().is_private());
 }
 
+#[test]
+fn unregistered_key_usage() {
+    let json = r#"{
+        "kty": "oct",
+        "k": "TdSBZdXL5n39JXlQc7QL3w",
+        "use": "x-vendor",
+        "key_ops": ["sign", "x-attest"]
+    }"#;
+    let jwk = JsonWebKey::from_str(json).unwrap();
+    assert_eq!(jwk.key_use, Some(KeyUse::Other("x-vendor".into())));
+    assert_eq!(
+        jwk.key_ops.iter().collect::<Vec<_>>(),
+        vec![KeyOp::Sign, KeyOp::Other("x-attest".into())]
+    );
+    assert!(jwk.key_ops.contains(KeyOps::SIGN));
+    assert!(!jwk.key_ops.contains(KeyOps::VERIFY));
+    assert_eq!(
+        serde_json::to_value(&jwk).unwrap(),
+        serde_json::from_str::<serde_json::Value>(json).unwrap()
+    );
+
+    assert!(matches!(
+        JsonWebKey::from_slice_strict(json),
+        Err(Error::UnregisteredKeyUse(key_use)) if key_use == "x-vendor"
+    ));
+    // A key with unregistered values doesn't fail the whole set.
+    let jwks: JwkSet = serde_json::from_str(&format!(r#"{{"keys": [{}]}}"#, json)).unwrap();
+    assert_eq!(jwks.keys().next(), Some(&jwk));
+
+    let json = json.replace(r#""use": "x-vendor","#, "");
+    assert!(matches!(
+        JsonWebKey::from_slice_strict(&json),
+        Err(Error::UnregisteredKeyOp(op)) if op == "x-attest"
+    ));
+    let json = json.replace(r#", "x-attest""#, "");
+    assert_eq!(
+        JsonWebKey::from_slice_strict(&json).unwrap().key_ops,
+        KeyOps::SIGN
+    );
+}
+
+#[test]
+fn key_usage_consistency() {
+    let json = r#"{
+        "kty": "EC",
+        "crv": "P-256",
+        "x": "QOMHmv96tVlJv-uNqprnDSKIj5AiLTXKRomXYnav0N0",
+        "y": "TjYZoHnctatEE6NCrKmXQdJJPnNzZEX8nBmZde3AY4k",
+        "use": "sig",
+        "key_ops": ["verify"]
+    }"#;
+    let mut jwk = JsonWebKey::from_str(json).unwrap();
+    assert_eq!(jwk.check_key_usage(), Ok(()));
+
+    let contradiction = KeyUsageError::ContradictsKeyUse {
+        key_use: KeyUse::Signing,
+        op: KeyOp::DeriveBits,
+    };
+    let json = json.replace(r#"["verify"]"#, r#"["sign", "deriveBits"]"#);
+    assert!(matches!(
+        JsonWebKey::from_str(&json),
+        Err(Error::KeyUsage(KeyUsageError::PrivateKeyRequired(
+            KeyOp::Sign
+        )))
+    ));
+    let (lenient, warnings) = JsonWebKey::from_slice_lenient(&json).unwrap();
+    assert_eq!(
+        warnings,
+        vec![
+            KeyUsageError::PrivateKeyRequired(KeyOp::Sign),
+            contradiction.clone(),
+            KeyUsageError::PrivateKeyRequired(KeyOp::DeriveBits),
+        ]
+    );
+    assert_eq!(lenient.key_ops, KeyOps::SIGN | KeyOps::DERIVE_BITS);
+    let jwks: JwkSet = serde_json::from_str(&format!(r#"{{"keys": [{}]}}"#, json)).unwrap();
+    assert_eq!(jwks.keys().count(), 0);
+    assert_eq!(jwks.unparsed().count(), 1);
+
+    assert!(matches!(
+        jwk.set_key_use(KeyUse::Encryption),
+        Err(Error::KeyUsage(KeyUsageError::ContradictsKeyUse { .. }))
+    ));
+    assert!(matches!(
+        jwk.set_key_ops(KeyOps::VERIFY | KeyOps::WRAP_KEY),
+        Err(Error::KeyUsage(KeyUsageError::ContradictsKeyUse {
+            op: KeyOp::WrapKey,
+            ..
+        }))
+    ));
+    assert_eq!(jwk.key_use, Some(KeyUse::Signing));
+    assert_eq!(jwk.key_ops, KeyOps::VERIFY);
+
+    let mut jwk = JsonWebKey::from_str(ED25519_JWK_FIXTURE).unwrap();
+    assert!(matches!(
+        jwk.set_key_ops(KeyOps::ENCRYPT),
+        Err(Error::KeyUsage(KeyUsageError::UnsupportedByKey(
+            KeyOp::Encrypt
+        )))
+    ));
+    jwk.set_key_ops(KeyOps::SIGN | KeyOps::VERIFY).unwrap();
+    jwk.set_key_use(KeyUse::Signing).unwrap();
+    assert_eq!(
+        contradiction.to_string(),
+        "the `deriveBits` key operation contradicts a `use` of `sig`"
+    );
+}
+
+#[test]
+fn typed_jwks() {
+    let private = PrivateJwk::from_str(P256_JWK_FIXTURE).unwrap();
+    assert!(matches!(
+        PublicJwk::from_str(P256_JWK_FIXTURE),
+        Err(Error::ExpectedPublicKey)
+    ));
+    assert!(serde_json::from_str::<PublicJwk>(P256_JWK_FIXTURE).is_err());
+    assert_eq!(
+        serde_json::from_str::<PrivateJwk>(P256_JWK_FIXTURE).unwrap(),
+        private
+    );
+
+    let public = private.to_public();
+    assert_eq!(*public.key, *private.key.to_public().unwrap());
+    assert_eq!(public.key_id.as_deref(), Some("a key"));
+    assert!(matches!(
+        PrivateJwk::from_str(&public.to_string()),
+        Err(Error::ExpectedPrivateKey)
+    ));
+    assert_eq!(PublicJwk::from_str(&public.to_string()).unwrap(), public);
+
+    let symmetric = r#"{"kty": "oct", "k": "TdSBZdXL5n39JXlQc7QL3w"}"#;
+    assert!(matches!(
+        PrivateJwk::from_str(symmetric),
+        Err(Error::ExpectedPrivateKey)
+    ));
+    assert!(matches!(
+        PublicJwk::from_str(symmetric),
+        Err(Error::ExpectedPublicKey)
+    ));
+
+    let mut jwk = JsonWebKey::from_str(P256_JWK_FIXTURE).unwrap();
+    jwk.set_key_ops(KeyOps::DECRYPT | KeyOps::UNWRAP_KEY | KeyOps::DERIVE_BITS)
+        .unwrap();
+    let public = PrivateJwk::try_from(jwk).unwrap().to_public();
+    assert_eq!(public.key_ops, KeyOps::ENCRYPT | KeyOps::WRAP_KEY);
+    assert_eq!(public.check_key_usage(), Ok(()));
+
+    let mut json: serde_json::Value = serde_json::from_str(&public.to_string()).unwrap();
+    json["alg"] = "RS256".into();
+    assert!(serde_json::from_value::<PublicJwk>(json.clone()).is_err());
+    assert!(matches!(
+        PublicJwk::from_str(&json.to_string()),
+        Err(Error::MismatchedAlgorithm)
+    ));
+    json["alg"] = "ECDH-ES".into();
+    json["key_ops"] = serde_json::json!(["unwrapKey"]);
+    assert!(matches!(
+        PublicJwk::from_str(&json.to_string()),
+        Err(Error::KeyUsage(KeyUsageError::PrivateKeyRequired(
+            KeyOp::UnwrapKey
+        )))
+    ));
+}
+
+#[cfg(feature = "pkcs-convert")]
+#[test]
+fn typed_jwk_pem() {
+    let full = PrivateJwk::from_str(RSA_2048_JWK_FIXTURE).unwrap();
+    let mut jwk = full.clone().into_inner();
+    if let Key::RSA {
+        private: Some(private),
+        ..
+    } = &mut *jwk.key
+    {
+        private.p = None;
+        private.q = None;
+        private.dp = None;
+        private.dq = None;
+        private.qi = None;
+    }
+    let d_only = PrivateJwk::try_from(jwk).unwrap();
+    assert_eq!(d_only.try_to_pem().unwrap(), full.key.to_pem());
+    assert_eq!(
+        d_only.to_public().to_pem(),
+        full.key.to_public().unwrap().to_pem()
+    );
+}
+
+#[cfg(feature = "jws")]
+#[test]
+fn typed_jwk_sign() {
+    let private = PrivateJwk::from_str(ED25519_JWK_FIXTURE).unwrap();
+    let token = private
+        .sign(&crate::jws::Header::new(Algorithm::EdDSA), "payload")
+        .unwrap();
+    assert_eq!(
+        crate::jws::verify(&token, &private.to_public())
+            .unwrap()
+            .payload,
+        b"payload"
+    );
+}
+
+#[test]
+fn extensions() {
+    let json = r#"{
+        "kty": "oct",
+        "k": "TdSBZdXL5n39JXlQc7QL3w",
+        "kid": "a key",
+        "ext": true,
+        "iat": 1700000000,
+        "x-vendor": {"tier": "gold"}
+    }"#;
+    let mut jwk = JsonWebKey::from_str(json).unwrap();
+    assert_eq!(jwk.key_id.as_deref(), Some("a key"));
+    assert_eq!(jwk.extensions.len(), 3);
+    assert_eq!(jwk.extensions.get::<bool>("ext").unwrap(), Some(true));
+    assert_eq!(jwk.extensions.get::<i64>("iat").unwrap(), Some(1700000000));
+    assert_eq!(jwk.extensions.get::<bool>("exp").unwrap(), None);
+    assert!(jwk.extensions.get::<String>("ext").is_err());
+    assert_eq!(
+        jwk.extensions.get_value("x-vendor"),
+        Some(&serde_json::json!({"tier": "gold"}))
+    );
+
+    let round_tripped = JsonWebKey::from_str(&jwk.to_string()).unwrap();
+    assert_eq!(round_tripped, jwk);
+    assert_eq!(
+        serde_json::to_value(&jwk).unwrap(),
+        serde_json::from_str::<serde_json::Value>(json).unwrap()
+    );
+
+    assert!(matches!(
+        jwk.extensions.check_understood(&["ext", "iat"]),
+        Err(Error::UnsupportedExtension(name)) if name == "x-vendor"
+    ));
+    assert!(jwk
+        .extensions
+        .check_understood(&["ext", "iat", "x-vendor"])
+        .is_ok());
+
+    jwk.extensions.insert("exp", 1800000000).unwrap();
+    assert_eq!(jwk.extensions.get::<i64>("exp").unwrap(), Some(1800000000));
+    assert!(matches!(
+        jwk.extensions.insert("kid", "another key"),
+        Err(Error::RegisteredMember(name)) if name == "kid"
+    ));
+
+    // Members of the key are not extensions.
+    for fixture in [P256_JWK_FIXTURE, RSA_JWK_FIXTURE, ED25519_JWK_FIXTURE] {
+        let jwk = JsonWebKey::from_str(fixture).unwrap();
+        assert!(jwk.extensions.is_empty());
+    }
+
+    // Nor are the members of other key types, which can't be inserted either.
+    let mut jwk = JsonWebKey::from_str(
+        r#"{"kty": "oct", "k": "TdSBZdXL5n39JXlQc7QL3w", "x": "QOMHmv96tVlJv-uNqprnDSKI"}"#,
+    )
+    .unwrap();
+    assert!(jwk.extensions.is_empty());
+    assert!(matches!(
+        jwk.extensions.insert("x", "QOMHmv96tVlJv-uNqprnDSKI"),
+        Err(Error::RegisteredMember(name)) if name == "x"
+    ));
+    assert!(serde_json::to_value(&jwk).unwrap().get("x").is_none());
+}
+
 #[test]
 fn x509_params() {
     let private_jwk = JsonWebKey::from_str(RSA_JWK_FIXTURE).unwrap();
@@ -31,6 +31,34 @@ fn ec_thumbprint() {
     );
 }
 
+#[test]
+fn p384_thumbprint() {
+    let jwk = JsonWebKey::from_str(P384_JWK_FIXTURE).unwrap();
+    assert_eq!(
+        jwk.key.thumbprint(),
+        "NP-baPEBSB7-pPXKArZ-H1-6etNy07uc8mcAJ3jdy7g"
+    );
+}
+
+#[test]
+fn secp256k1_thumbprint() {
+    let jwk = JsonWebKey::from_str(SECP256K1_JWK_FIXTURE).unwrap();
+    assert_eq!(
+        jwk.key.thumbprint(),
+        "m3eepThHtuKGIm1iHkMla9ydpwkKwtwsBdJQa55U4-k"
+    );
+}
+
+#[test]
+fn okp_thumbprint() {
+    let jwk = JsonWebKey::from_str(ED25519_JWK_FIXTURE).unwrap();
+    assert_eq!(
+        jwk.key.thumbprint(),
+        // From https://tools.ietf.org/html/rfc8037#appendix-A.3
+        "kPrK_qmxVWaYVA9wwBF6Iuo3vVzz7TxHCTwXBygrS4k"
+    );
+}
+
 #[test]
 fn symmetric_thumbprint() {
     let jwk = JsonWebKey::from_str(OCT_JWK_FIXTURE).unwrap();
@@ -0,0 +1,160 @@
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "pkcs-convert")]
+use crate::ConversionError;
+use crate::{Error, JsonWebKey, Key, KeyOp, KeyOps};
+
+/// A `JsonWebKey` holding a private asymmetric key.
+/// Deserializing a public or symmetric key as a `PrivateJwk` fails, as does a key whose `alg`,
+/// `use`, or `key_ops` don't fit the key.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "JsonWebKey", into = "JsonWebKey")]
+pub struct PrivateJwk(pub(crate) JsonWebKey);
+
+/// A `JsonWebKey` holding a public key, which is safe to publish, e.g., in a `PublicJwkSet`.
+/// Deserializing a private or symmetric key as a `PublicJwk` fails, as does a key whose `alg`,
+/// `use`, or `key_ops` don't fit the key.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "JsonWebKey", into = "JsonWebKey")]
+pub struct PublicJwk(pub(crate) JsonWebKey);
+
+impl PrivateJwk {
+    /// Returns the public key, with the same parameters except that the private operations in
+    /// `key_ops` are replaced by their public counterparts (e.g., `sign` by `verify`).
+    /// `deriveKey` and `deriveBits` have no counterpart and are dropped.
+    pub fn to_public(&self) -> PublicJwk {
+        let key = self.0.key.to_public().expect("asymmetric key").into_owned();
+        let key_ops = self
+            .0
+            .key_ops
+            .iter()
+            .filter_map(|op| match op {
+                KeyOp::Sign | KeyOp::Verify => Some(KeyOp::Verify),
+                KeyOp::Decrypt | KeyOp::Encrypt => Some(KeyOp::Encrypt),
+                KeyOp::UnwrapKey | KeyOp::WrapKey => Some(KeyOp::WrapKey),
+                KeyOp::DeriveKey | KeyOp::DeriveBits => None,
+                op @ KeyOp::Other(_) => Some(op),
+            })
+            .collect::<KeyOps>();
+        PublicJwk(JsonWebKey {
+            key: Box::new(key),
+            key_ops,
+            ..self.0.clone()
+        })
+    }
+
+    /// Signs `payload` and returns the JWS compact serialization, as per `jws::sign`.
+    #[cfg(feature = "jws")]
+    pub fn sign(
+        &self,
+        header: &crate::jws::Header,
+        payload: impl AsRef<[u8]>,
+    ) -> Result<String, crate::jws::Error> {
+        crate::jws::sign(header, payload, &self.0)
+    }
+
+    /// Encodes the key as PKCS#8 with PEM armoring, as per `Key::try_to_pem`.
+    /// Fails only if the RSA parameters are inconsistent.
+    #[cfg(feature = "pkcs-convert")]
+    pub fn try_to_pem(&self) -> Result<String, ConversionError> {
+        self.0.key.try_to_pem()
+    }
+
+    pub fn into_inner(self) -> JsonWebKey {
+        self.0
+    }
+}
+
+impl PublicJwk {
+    /// Encodes the key as PKCS#8 with PEM armoring.
+    #[cfg(feature = "pkcs-convert")]
+    pub fn to_pem(&self) -> String {
+        self.0.key.to_pem()
+    }
+
+    pub fn into_inner(self) -> JsonWebKey {
+        self.0
+    }
+}
+
+impl TryFrom<JsonWebKey> for PrivateJwk {
+    type Error = Error;
+
+    fn try_from(jwk: JsonWebKey) -> Result<Self, Self::Error> {
+        if matches!(*jwk.key, Key::Symmetric { .. }) || !jwk.key.is_private() {
+            return Err(Error::ExpectedPrivateKey);
+        }
+        check_parameters(&jwk)?;
+        Ok(Self(jwk))
+    }
+}
+
+impl TryFrom<JsonWebKey> for PublicJwk {
+    type Error = Error;
+
+    fn try_from(jwk: JsonWebKey) -> Result<Self, Self::Error> {
+        if jwk.key.is_private() {
+            return Err(Error::ExpectedPublicKey);
+        }
+        check_parameters(&jwk)?;
+        Ok(Self(jwk))
+    }
+}
+
+/// Checks that `alg`, `use`, and `key_ops` are consistent with the key, as
+/// `JsonWebKey::from_slice_strict` does.
+fn check_parameters(jwk: &JsonWebKey) -> Result<(), Error> {
+    if let Some(alg) = jwk.algorithm {
+        JsonWebKey::validate_key_algorithm(alg, &jwk.key)?;
+    }
+    jwk.check_key_usage()?;
+    Ok(())
+}
+
+impl From<PrivateJwk> for JsonWebKey {
+    fn from(jwk: PrivateJwk) -> Self {
+        jwk.0
+    }
+}
+
+impl From<PublicJwk> for JsonWebKey {
+    fn from(jwk: PublicJwk) -> Self {
+        jwk.0
+    }
+}
+
+impl std::ops::Deref for PrivateJwk {
+    type Target = JsonWebKey;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl std::ops::Deref for PublicJwk {
+    type Target = JsonWebKey;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl std::str::FromStr for PrivateJwk {
+    type Err = Error;
+    fn from_str(json: &str) -> Result<Self, Self::Err> {
+        json.parse::<JsonWebKey>()?.try_into()
+    }
+}
+
+impl std::str::FromStr for PublicJwk {
+    type Err = Error;
+    fn from_str(json: &str) -> Result<Self, Self::Err> {
+        json.parse::<JsonWebKey>()?.try_into()
+    }
+}
+
+impl std::fmt::Display for PublicJwk {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
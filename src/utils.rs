@@ -12,7 +12,7 @@ pub(crate) fn base64_encode(bytes: impl AsRef<[u8]>) -> String {
     base64::encode_config(bytes, base64_config())
 }
 
-fn base64_decode(b64: impl AsRef<[u8]>) -> Result<Vec<u8>, base64::DecodeError> {
+pub(crate) fn base64_decode(b64: impl AsRef<[u8]>) -> Result<Vec<u8>, base64::DecodeError> {
     base64::decode_config(b64, base64_config())
 }
 
@@ -38,6 +38,36 @@ pub(crate) mod serde_base64 {
     }
 }
 
+#[cfg(any(feature = "jwe", feature = "jws"))]
+pub(crate) mod rsa_keys {
+    use rsa::{BigUint, RsaPrivateKey, RsaPublicKey};
+
+    use crate::{RsaPrivate, RsaPublic};
+
+    pub(crate) fn public_key(public: &RsaPublic) -> Option<RsaPublicKey> {
+        RsaPublicKey::new(
+            BigUint::from_bytes_be(&public.n),
+            BigUint::from_bytes_be(&public.e),
+        )
+        .ok()
+    }
+
+    pub(crate) fn private_key(public: &RsaPublic, private: &RsaPrivate) -> Option<RsaPrivateKey> {
+        // When the primes are missing, they're recovered from `d`.
+        let primes = match (&private.p, &private.q) {
+            (Some(p), Some(q)) => vec![BigUint::from_bytes_be(p), BigUint::from_bytes_be(q)],
+            _ => Vec::new(),
+        };
+        RsaPrivateKey::from_components(
+            BigUint::from_bytes_be(&public.n),
+            BigUint::from_bytes_be(&public.e),
+            BigUint::from_bytes_be(&private.d),
+            primes,
+        )
+        .ok()
+    }
+}
+
 #[cfg(feature = "pkcs-convert")]
 pub(crate) mod pkcs8 {
     use yasna::{
@@ -56,7 +86,7 @@ pub(crate) mod pkcs8 {
 
     pub(crate) fn write_private(
         oids: &[Option<&ObjectIdentifier>],
-        body_writer: impl FnOnce(&mut DERWriterSeq<'_>),
+        body_writer: impl FnOnce(DERWriter<'_>),
     ) -> Vec<u8> {
         yasna::construct_der(|writer| {
             writer.write_sequence(|writer| {
@@ -65,7 +95,7 @@ pub(crate) mod pkcs8 {
                     .next()
                     .write_sequence(|writer| write_oids(writer, oids));
 
-                let body = yasna::construct_der(|writer| writer.write_sequence(body_writer));
+                let body = yasna::construct_der(body_writer);
                 writer
                     .next()
                     .write_tagged_der(&TaggedDerValue::from_octetstring(body));